@@ -0,0 +1,86 @@
+//! Birthdays of well-known people, for `--famous`
+
+use color_eyre::eyre::{eyre, Result};
+
+/// Lowercase names and ISO 8601 birthdays, sorted by name for binary search
+const BIRTHDAYS: &[(&str, &str)] = &[
+    ("ada lovelace", "1815-12-10"),
+    ("alan turing", "1912-06-23"),
+    ("albert einstein", "1879-03-14"),
+    ("charles darwin", "1809-02-12"),
+    ("frida kahlo", "1907-07-06"),
+    ("grace hopper", "1906-12-09"),
+    ("isaac newton", "1643-01-04"),
+    ("jane austen", "1775-12-16"),
+    ("leonardo da vinci", "1452-04-15"),
+    ("marie curie", "1867-11-07"),
+    ("nelson mandela", "1918-07-18"),
+    ("william shakespeare", "1564-04-23"),
+];
+
+/// The birthday of `name`, ignoring case, or an error suggesting the closest name
+pub fn birthday(name: &str) -> Result<&'static str> {
+    let name = name.trim().to_lowercase();
+    if let Ok(i) = BIRTHDAYS.binary_search_by_key(&name.as_str(), |&(known, _)| known) {
+        return Ok(BIRTHDAYS[i].1);
+    }
+    let (closest, _) = BIRTHDAYS
+        .iter()
+        .min_by_key(|(known, _)| edit_distance(&name, known))
+        .expect("the table isn't empty");
+    Err(eyre!(
+        "No birthday known for '{}'. Did you mean '{}'?",
+        name,
+        closest
+    ))
+}
+
+/// Levenshtein distance, counting single character insertions, deletions, and
+/// substitutions
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn table_is_sorted() {
+        assert!(BIRTHDAYS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn known_names() {
+        assert_eq!(birthday("Ada Lovelace").unwrap(), "1815-12-10");
+        assert_eq!(birthday("alan turing").unwrap(), "1912-06-23");
+        assert_eq!(birthday(" MARIE CURIE ").unwrap(), "1867-11-07");
+    }
+
+    #[test]
+    fn unknown_names_suggest_the_closest() {
+        let err = birthday("Alan Turning").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No birthday known for 'alan turning'. Did you mean 'alan turing'?"
+        );
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
@@ -0,0 +1,6064 @@
+//! Calculate how old you are
+
+use chrono::{
+    DateTime, Local, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+    Utc,
+};
+use chrono::{Datelike, Weekday};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use color_eyre::eyre::{bail, eyre, Result, WrapErr};
+use directories::{BaseDirs, ProjectDirs};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::str;
+
+#[cfg(feature = "famous")]
+mod famous;
+mod zodiac;
+
+pub use zodiac::Zodiac;
+
+pub const THIS_PROGRAM_NAME: &str = env!("CARGO_PKG_NAME");
+
+const BIRTHDAY_ENV_VAR: &str = "HOWOLDAMI_BIRTHDAY";
+const FORMAT_ENV_VAR: &str = "HOWOLDAMI_FORMAT";
+
+/// Errors that scripts may want to tell apart by exit code
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    MissingBirthday,
+}
+
+impl ConfigError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ConfigError::MissingBirthday => EXIT_MISSING_BIRTHDAY,
+        }
+    }
+
+    /// A stable name for `--error-format json`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConfigError::MissingBirthday => "missing_birthday",
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::MissingBirthday => write!(
+                f,
+                "No birthday specified in either config or command line args"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The exit code for any error that isn't a [`ConfigError`]
+pub const EXIT_FAILURE: i32 = 1;
+
+/// The exit code for `--notify-on-birthday` on any other day
+pub const EXIT_NOT_BIRTHDAY: i32 = 3;
+
+/// The exit code when no birthday is configured. clap already exits with 2 for usage
+/// errors, so this is kept apart from it
+pub const EXIT_MISSING_BIRTHDAY: i32 = 4;
+
+/// Pick the process exit code for an error returned while running
+pub fn exit_code(err: &color_eyre::Report) -> i32 {
+    match err.downcast_ref::<ConfigError>() {
+        Some(err) => err.exit_code(),
+        None => EXIT_FAILURE,
+    }
+}
+
+/// Whether `age` is within both optional inclusive bounds
+pub fn within_bounds(age: i64, at_least: Option<i64>, at_most: Option<i64>) -> bool {
+    at_least.is_none_or(|min| age >= min) && at_most.is_none_or(|max| age <= max)
+}
+
+/// How errors are printed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// A readable report with a backtrace
+    #[default]
+    Human,
+    /// A single JSON object like {"error": "...", "kind": "missing_birthday"}
+    Json,
+}
+
+/// An error as a JSON object. `kind` is "error" for anything that isn't a [`ConfigError`]
+pub fn error_json(err: &color_eyre::Report) -> String {
+    error_value(err).to_string()
+}
+
+fn error_value(err: &color_eyre::Report) -> serde_json::Value {
+    let kind = err
+        .downcast_ref::<ConfigError>()
+        .map_or("error", ConfigError::kind);
+    serde_json::json!({ "error": format!("{:#}", err), "kind": kind })
+}
+
+/// Read an environment variable, treating unset as `None` but anything unusable as an error
+fn env_var(key: &str) -> Result<Option<String>> {
+    match std::env::var(key) {
+        Ok(value) if value.trim().is_empty() => bail!("{} is set but empty", key),
+        Ok(value) => Ok(Some(value)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(err) => bail!("Could not read {}: {}", key, err),
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Quiet = 1,
+    #[default]
+    Normal = 2,
+    Verbose = 3,
+    /// Reserved for diagnostics. For now this prints what `Verbose` does
+    Debug = 4,
+}
+
+impl Verbosity {
+    /// The level for a number of `-v` flags
+    pub fn from_count(count: u8) -> Self {
+        match count {
+            0 => Verbosity::Normal,
+            1 => Verbosity::Verbose,
+            _ => Verbosity::Debug,
+        }
+    }
+}
+
+/// Formats with a spelled out month that are always accepted. Chrono matches both
+/// full and abbreviated month names, case-insensitively, for either of `%B`/`%b`
+const MONTH_NAME_FORMATS: &[&str] = &["%B %e %Y", "%B %e, %Y", "%e %B %Y", "%e %B, %Y"];
+
+fn parse_full_date(date: &str, format_str: &str) -> Result<NaiveDate> {
+    if let Some(datetime) = parse_epoch(date) {
+        return datetime.map(|datetime| datetime.date());
+    }
+    // Unambiguous ISO 8601 dates are accepted regardless of the configured format
+    if let Ok(date) = date.parse::<NaiveDate>() {
+        return Ok(date);
+    }
+    if let Some(date) = parse_ordinal_date(date) {
+        return date;
+    }
+    let configured_err = match NaiveDate::parse_from_str(date, format_str) {
+        Ok(date) => return Ok(date),
+        Err(err) => err,
+    };
+    let date = strip_ordinal_suffixes(date);
+    for format in MONTH_NAME_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(&date, format) {
+            return Ok(date);
+        }
+    }
+    // Report the failure against the format the user asked for
+    Err(configured_err.into())
+}
+
+/// Parse Unix epoch seconds written like "@1672531200" as a UTC date and time. `None`
+/// if `date` doesn't start with "@"
+fn parse_epoch(date: &str) -> Option<Result<NaiveDateTime>> {
+    let seconds = date.trim().strip_prefix('@')?;
+    Some(
+        seconds
+            .parse::<i64>()
+            .ok()
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+            .map(|datetime| datetime.naive_utc())
+            .ok_or_else(|| eyre!("Invalid epoch timestamp: {}", date)),
+    )
+}
+
+/// Drop English ordinal suffixes from numbers, so "1st Jan 1998" reads as "1 Jan 1998".
+/// Any of st, nd, rd, and th is accepted after any number, so "2st" is still the 2nd
+fn strip_ordinal_suffixes(date: &str) -> String {
+    date.split(' ')
+        .map(|word| {
+            let (word, comma) = match word.strip_suffix(',') {
+                Some(word) => (word, ","),
+                None => (word, ""),
+            };
+            let digits = word.bytes().take_while(u8::is_ascii_digit).count();
+            let suffix = &word[digits..];
+            if digits > 0
+                && ["st", "nd", "rd", "th"]
+                    .iter()
+                    .any(|ordinal| suffix.eq_ignore_ascii_case(ordinal))
+            {
+                format!("{}{}", &word[..digits], comma)
+            } else {
+                format!("{}{}", word, comma)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse an ISO 8601 ordinal date like "1998-032", the 32nd day of 1998. `None`
+/// if `date` isn't shaped like one, so other formats can be tried
+fn parse_ordinal_date(date: &str) -> Option<Result<NaiveDate>> {
+    let (year, day) = date.split_once('-')?;
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if year.len() != 4 || day.len() != 3 || !all_digits(year) || !all_digits(day) {
+        return None;
+    }
+    let year: i32 = year.parse().ok()?;
+    let day: u32 = day.parse().ok()?;
+    let days_in_year = if is_leap_year(year) { 366 } else { 365 };
+    if !(1..=days_in_year).contains(&day) {
+        return Some(Err(eyre!(
+            "Day of year {} is out of range for {}, which has {} days",
+            day,
+            year,
+            days_in_year
+        )));
+    }
+    Some(NaiveDate::from_yo_opt(year, day).ok_or_else(|| eyre!("Invalid date: {}", date)))
+}
+
+/// Parse an offset from `today` like "+30d" or "-1y", in days, weeks, months, or
+/// years. `None` if `expr` doesn't start with a sign, so it can be read as a date
+fn parse_relative_date(expr: &str, today: NaiveDate) -> Option<Result<NaiveDate>> {
+    let expr = expr.trim();
+    let (negative, rest) = match expr.as_bytes().first()? {
+        b'+' => (false, &expr[1..]),
+        b'-' => (true, &expr[1..]),
+        _ => return None,
+    };
+    let invalid = || {
+        eyre!(
+            "Invalid relative date '{}', expected something like +30d or -1y",
+            expr
+        )
+    };
+    let Some(unit) = rest.chars().last() else {
+        return Some(Err(invalid()));
+    };
+    let Ok(count) = rest[..rest.len() - unit.len_utf8()].parse::<u32>() else {
+        return Some(Err(invalid()));
+    };
+    let date = match unit {
+        'd' | 'w' => {
+            let days = if unit == 'w' {
+                count as i64 * 7
+            } else {
+                count as i64
+            };
+            let offset = chrono::Duration::try_days(days);
+            offset.and_then(|offset| {
+                if negative {
+                    today.checked_sub_signed(offset)
+                } else {
+                    today.checked_add_signed(offset)
+                }
+            })
+        }
+        'm' | 'y' => {
+            let months = if unit == 'y' {
+                count.checked_mul(12)
+            } else {
+                Some(count)
+            };
+            months.map(Months::new).and_then(|months| {
+                if negative {
+                    today.checked_sub_months(months)
+                } else {
+                    today.checked_add_months(months)
+                }
+            })
+        }
+        _ => return Some(Err(invalid())),
+    };
+    Some(date.ok_or_else(|| eyre!("Relative date '{}' is out of range", expr)))
+}
+
+/// Split a trailing `HH:MM` or `HH:MM:SS` time of day off of a date string
+fn split_time(date: &str) -> (&str, Option<NaiveTime>) {
+    if let Some((date_part, time_part)) = date.trim().rsplit_once(' ') {
+        for format in ["%H:%M", "%H:%M:%S"] {
+            if let Ok(time) = NaiveTime::parse_from_str(time_part, format) {
+                return (date_part.trim_end(), Some(time));
+            }
+        }
+    }
+    (date, None)
+}
+
+/// Roughly the worldwide life expectancy at birth
+pub const DEFAULT_LIFE_EXPECTANCY: u32 = 73;
+
+/// Two digit years at or above the pivot are in the 1900s, below it in the 2000s
+pub const DEFAULT_CENTURY_PIVOT: u32 = 30;
+
+/// How to interpret a [`DateSpecifier`]
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub format_str: String,
+    pub century_pivot: u32,
+    /// Reject dates the format reads but that would also be valid with the day and
+    /// month swapped
+    pub strict: bool,
+    /// Clamp years chrono can't represent instead of rejecting them
+    pub clamp_years: bool,
+}
+
+impl ParseOptions {
+    pub fn new(format_str: &str) -> Self {
+        Self {
+            format_str: format_str.to_owned(),
+            century_pivot: DEFAULT_CENTURY_PIVOT,
+            strict: false,
+            clamp_years: false,
+        }
+    }
+}
+
+/// Error if `date` was read with `format_str` and swapping its day and month gives
+/// another valid date. Formats starting with the year are never ambiguous, nor are
+/// ISO 8601 and month name dates
+fn reject_ambiguous(input: &str, date: NaiveDate, format_str: &str) -> Result<()> {
+    let read_with_format = NaiveDate::parse_from_str(input, format_str).is_ok();
+    if read_with_format
+        && !format_str.starts_with("%Y")
+        && date.day() <= 12
+        && date.day() != date.month()
+    {
+        bail!(
+            "'{}' is ambiguous, since its day and month could be swapped. Write it as \
+             {} to confirm",
+            input,
+            date
+        );
+    }
+    Ok(())
+}
+
+/// Parse a year, reading two digit years like "98" relative to `century_pivot`.
+/// Negative years are astronomical, so "0" is 1 BCE and "-44" is 45 BCE. Years
+/// outside what `NaiveDate` supports are clamped or rejected, per `clamp_years`
+fn expand_year(year: &str, options: &ParseOptions) -> Result<i32> {
+    use std::num::IntErrorKind;
+
+    let parsed = match year.parse::<i64>() {
+        Ok(parsed) => parsed,
+        // Too many digits for an i64 is still just out of range
+        Err(err) if *err.kind() == IntErrorKind::PosOverflow => i64::MAX,
+        Err(err) if *err.kind() == IntErrorKind::NegOverflow => i64::MIN,
+        Err(err) => return Err(eyre!("Invalid year '{}': {}", year, err)),
+    };
+    if year.len() == 2 && year.bytes().all(|b| b.is_ascii_digit()) {
+        let century = if parsed as u32 >= options.century_pivot {
+            1900
+        } else {
+            2000
+        };
+        return Ok(century + parsed as i32);
+    }
+    let (min, max) = (NaiveDate::MIN.year(), NaiveDate::MAX.year());
+    match i32::try_from(parsed) {
+        Ok(parsed) if (min..=max).contains(&parsed) => Ok(parsed),
+        _ if options.clamp_years => Ok(parsed.clamp(min.into(), max.into()) as i32),
+        _ => bail!(
+            "Year {} is out of the supported range, {} to {}. Pass --clamp-years to use the \
+             nearest supported year",
+            year,
+            min,
+            max
+        ),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DateSpecifier {
+    Full(String),
+    YearOnly(String),
+    /// A year and month like "1998-01", for when the day isn't known
+    MonthYear(String),
+}
+
+impl DateSpecifier {
+    pub fn year(year: &str) -> Self {
+        Self::YearOnly(year.to_owned())
+    }
+
+    pub fn full(date: &str) -> Self {
+        Self::Full(date.to_owned())
+    }
+
+    pub fn month_year(month_year: &str) -> Self {
+        Self::MonthYear(month_year.to_owned())
+    }
+
+    pub fn to_naive_date(&self, format_str: &str) -> Result<NaiveDate> {
+        self.to_naive_date_with(&ParseOptions::new(format_str))
+    }
+
+    pub fn to_naive_date_with(&self, options: &ParseOptions) -> Result<NaiveDate> {
+        let date = match self {
+            DateSpecifier::Full(date) => {
+                let input = split_time(date).0;
+                let date = parse_full_date(input, &options.format_str)?;
+                if options.strict {
+                    reject_ambiguous(input, date, &options.format_str)?;
+                }
+                date
+            }
+            DateSpecifier::YearOnly(year) => {
+                let year = expand_year(year, options)?;
+                // Default to January 1st for evaluation purposes
+                NaiveDate::from_yo_opt(year, 1).ok_or_else(|| eyre!("Invalid year: {}", year))?
+            }
+            DateSpecifier::MonthYear(month_year) => {
+                let invalid = || eyre!("Invalid month and year '{}', expected YYYY-MM", month_year);
+                let (year, month) = month_year.rsplit_once('-').ok_or_else(invalid)?;
+                let year = expand_year(year, options)?;
+                let month = month.parse::<u32>().map_err(|_| invalid())?;
+                // Default to the 1st, like a year alone defaults to January 1st
+                NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(invalid)?
+            }
+        };
+        Ok(date)
+    }
+
+    /// The time of day, if one was given after the date
+    pub fn time(&self) -> Option<NaiveTime> {
+        match self {
+            DateSpecifier::Full(date) => match parse_epoch(date) {
+                Some(datetime) => datetime.ok().map(|datetime| datetime.time()),
+                None => split_time(date).1,
+            },
+            DateSpecifier::YearOnly(_) | DateSpecifier::MonthYear(_) => None,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        match self {
+            DateSpecifier::Full(_) => true,
+            DateSpecifier::YearOnly(_) | DateSpecifier::MonthYear(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for DateSpecifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DateSpecifier::Full(date) => write!(f, "{}", date),
+            DateSpecifier::YearOnly(year) => write!(f, "{}", year),
+            DateSpecifier::MonthYear(month_year) => write!(f, "{}", month_year),
+        }
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Deserialize)]
+pub enum DateFormat {
+    /// Month, day, year
+    MDY { separator: String },
+    /// Day, month, year
+    DMY { separator: String },
+    /// Year, month, day
+    YMD { separator: String },
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        DateFormat::MDY {
+            separator: "/".into(),
+        }
+    }
+}
+
+/// The format as it is written for `--format`, like "MDY/". Use
+/// [`DateFormat::as_fmt_string`] for the chrono pattern
+impl fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DateFormat::MDY { separator } => write!(f, "MDY{}", separator),
+            DateFormat::DMY { separator } => write!(f, "DMY{}", separator),
+            DateFormat::YMD { separator } => write!(f, "YMD{}", separator),
+        }
+    }
+}
+
+/// One line per sample `--format` value, with a date written in it, like
+/// "MDY/   12/31/1998". Any separator string works, not just these
+pub fn format_examples() -> Vec<String> {
+    let sample = NaiveDate::from_ymd_opt(1998, 12, 31).unwrap();
+    let formats = [
+        ("MDY", "/"),
+        ("MDY", "-"),
+        ("DMY", "/"),
+        ("DMY", "."),
+        ("YMD", "-"),
+        ("YMD", "/"),
+    ];
+    formats
+        .into_iter()
+        .map(|(order, separator)| {
+            let input = format!("{}{}", order, separator);
+            let format: DateFormat = input.parse().expect("sample formats are valid");
+            format!("{:<7}{}", input, sample.format(&format.as_fmt_string()))
+        })
+        .collect()
+}
+
+impl str::FromStr for DateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let first = chars.next().ok_or("No first character found")?;
+        let second = chars.next().ok_or("No second character found")?;
+        let third = chars.next().ok_or("No third character found")?;
+        // Everything after the field order is the separator, so it may be several characters
+        let separator = chars.as_str();
+        if separator.is_empty() {
+            return Err("No separator found".to_owned());
+        }
+        let separator = separator.to_owned();
+
+        let format = match (first, second, third) {
+            ('M', 'D', 'Y') => DateFormat::MDY { separator },
+            ('D', 'M', 'Y') => DateFormat::DMY { separator },
+            ('Y', 'M', 'D') => DateFormat::YMD { separator },
+            _ => return Err("Invalid date format".to_owned()),
+        };
+        Ok(format)
+    }
+}
+
+/// The `--format` value that infers the format from the birthday
+const DETECT_FORMAT: &str = "detect";
+
+/// Named shorthands for common [`DateFormat`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FormatPreset {
+    /// MDY/, like 12/31/1998
+    Us,
+    /// YMD-, like 1998-12-31
+    Iso,
+    /// DMY., like 31.12.1998
+    Euro,
+}
+
+impl FormatPreset {
+    pub fn format(self) -> DateFormat {
+        match self {
+            FormatPreset::Us => DateFormat::MDY {
+                separator: "/".into(),
+            },
+            FormatPreset::Iso => DateFormat::YMD {
+                separator: "-".into(),
+            },
+            FormatPreset::Euro => DateFormat::DMY {
+                separator: ".".into(),
+            },
+        }
+    }
+}
+
+/// Keep a literal `%` in a separator from being read as a chrono specifier
+fn escape_fmt(separator: &str) -> String {
+    separator.replace('%', "%%")
+}
+
+impl DateFormat {
+    /// Infer the field order and separator from a numeric date like "31/12/1998".
+    /// A four digit first field is a year and a first field over 12 is a day. A
+    /// second field over 12 is a day after a month. `None` if the order is ambiguous
+    pub fn detect(sample: &str) -> Option<Self> {
+        let sample = sample.trim();
+        let first_end = sample.find(|c: char| !c.is_ascii_digit())?;
+        let rest = &sample[first_end..];
+        let second_start = rest.find(|c: char| c.is_ascii_digit())?;
+        let separator = rest[..second_start].to_owned();
+        let fields: Vec<&str> = sample.split(separator.as_str()).collect();
+        let [first, second, third] = fields[..] else {
+            return None;
+        };
+        let first_value: u32 = first.parse().ok()?;
+        let second_value: u32 = second.parse().ok()?;
+        third.parse::<u32>().ok()?;
+
+        if first.len() == 4 {
+            Some(DateFormat::YMD { separator })
+        } else if first_value > 12 && second_value <= 12 {
+            Some(DateFormat::DMY { separator })
+        } else if second_value > 12 && first_value <= 12 {
+            Some(DateFormat::MDY { separator })
+        } else {
+            None
+        }
+    }
+
+    /// A sensible format for a locale tag like "en-US" or "ja_JP", going by the
+    /// region where one is given and the language otherwise
+    pub fn for_locale(tag: &str) -> Option<Self> {
+        let tag = tag.replace('_', "-");
+        let mut parts = tag.split('-');
+        let language = parts.next()?.to_ascii_lowercase();
+        let region = parts.next_back().map(|region| region.to_ascii_uppercase());
+
+        let by_region = match region.as_deref() {
+            Some("US") => Some(DateFormat::MDY {
+                separator: "/".into(),
+            }),
+            Some("JP" | "TW" | "ZA") => Some(DateFormat::YMD {
+                separator: "/".into(),
+            }),
+            Some("CN" | "SE" | "LT") => Some(DateFormat::YMD {
+                separator: "-".into(),
+            }),
+            Some("KR" | "HU") => Some(DateFormat::YMD {
+                separator: ".".into(),
+            }),
+            Some("DE" | "AT" | "CH" | "RU" | "PL" | "CZ" | "FI" | "NO" | "DK" | "TR" | "UA") => {
+                Some(DateFormat::DMY {
+                    separator: ".".into(),
+                })
+            }
+            Some("GB" | "IE" | "AU" | "NZ" | "IN" | "FR" | "ES" | "IT" | "BR" | "MX") => {
+                Some(DateFormat::DMY {
+                    separator: "/".into(),
+                })
+            }
+            Some("NL" | "BE") => Some(DateFormat::DMY {
+                separator: "-".into(),
+            }),
+            _ => None,
+        };
+        if by_region.is_some() {
+            return by_region;
+        }
+        match language.as_str() {
+            "en" => Some(DateFormat::MDY {
+                separator: "/".into(),
+            }),
+            "ja" | "zh" => Some(DateFormat::YMD {
+                separator: "/".into(),
+            }),
+            "ko" | "hu" => Some(DateFormat::YMD {
+                separator: ".".into(),
+            }),
+            "sv" | "lt" => Some(DateFormat::YMD {
+                separator: "-".into(),
+            }),
+            "de" | "ru" | "pl" | "cs" | "fi" | "nb" | "da" | "tr" | "uk" => Some(DateFormat::DMY {
+                separator: ".".into(),
+            }),
+            "fr" | "es" | "it" | "pt" => Some(DateFormat::DMY {
+                separator: "/".into(),
+            }),
+            "nl" => Some(DateFormat::DMY {
+                separator: "-".into(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn as_fmt_string(&self) -> String {
+        match self {
+            DateFormat::MDY { separator } => {
+                let separator = escape_fmt(separator);
+                format!("%m{}%d{}%Y", separator, separator)
+            }
+            DateFormat::DMY { separator } => {
+                let separator = escape_fmt(separator);
+                format!("%d{}%m{}%Y", separator, separator)
+            }
+            DateFormat::YMD { separator } => {
+                let separator = escape_fmt(separator);
+                format!("%Y{}%m{}%d", separator, separator)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    birthday: Option<String>,
+    birthyear: Option<String>,
+    current_date: Option<String>,
+    current_year: Option<String>,
+    format: Option<DateFormat>,
+    verbosity: Option<Verbosity>,
+    greeting: Option<String>,
+    life_expectancy: Option<u32>,
+    #[serde(default)]
+    person: Vec<PersonConfig>,
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileConfig>,
+    /// Keys serde ignored, kept so typos can be reported
+    #[serde(skip)]
+    unknown_keys: Vec<String>,
+}
+
+const CONFIG_KEYS: &[&str] = &[
+    "birthday",
+    "birthyear",
+    "current_date",
+    "current_year",
+    "format",
+    "verbosity",
+    "greeting",
+    "life_expectancy",
+    "person",
+    "profiles",
+];
+
+const PERSON_KEYS: &[&str] = &["name", "birthday", "birthyear"];
+
+const PROFILE_KEYS: &[&str] = &["birthday", "birthyear", "format"];
+
+/// A `[[person]]` entry in the config file
+#[derive(Debug, Deserialize)]
+struct PersonConfig {
+    name: String,
+    birthday: Option<String>,
+    birthyear: Option<String>,
+}
+
+/// A `[profiles.<name>]` table in the config file, chosen with `--profile`
+#[derive(Debug, Deserialize)]
+struct ProfileConfig {
+    birthday: Option<String>,
+    birthyear: Option<String>,
+    format: Option<DateFormat>,
+}
+
+/// An annotated example of every key `config.toml` understands
+pub const CONFIG_TEMPLATE: &str = r#"# howoldami config file. The path given with --config is read if there is one.
+# Otherwise the first of these that exists is read:
+#   $XDG_CONFIG_HOME/howoldami/config.toml
+#   config.toml in your platform's config directory (e.g. ~/.config/howoldami/config.toml)
+#   ./howoldami.toml
+# Every key is optional, and command line flags override anything set here.
+
+# string: your birthday, read with `format`. ISO 8601 dates like "1998-01-01" always work
+birthday = "01/01/1998"
+
+# string: just your birth year, used only when `birthday` isn't set
+# birthyear = "1998"
+
+# string: pin today's date, for reproducible results. --date, --year, and --at override it
+# current_date = "06/01/2024"
+
+# string: pin just today's year, used only when `current_date` isn't set
+# current_year = "2024"
+
+# table: how dates are written. One of MDY, DMY, or YMD, with a separator string
+format = { MDY = { separator = "/" } }
+
+# string: "quiet", "normal", "verbose", or "debug"
+verbosity = "normal"
+
+# string: replaces "Happy birthday!". {age} and {name} are filled in
+greeting = "Happy {age}th birthday, {name}!"
+
+# integer: the years --life-expectancy measures against when given no value
+# life_expectancy = 73
+
+# array of tables: list each person's age instead of a single birthday.
+# Each entry needs a `name` and either a `birthday` or a `birthyear`
+[[person]]
+name = "Alice"
+birthday = "01/01/1998"
+
+[[person]]
+name = "Bob"
+birthyear = "2000"
+
+# tables: named identities picked with --profile, each overriding the birthday,
+# birthyear, and format above
+[profiles.work]
+birthday = "1998-01-01"
+
+[profiles.home]
+birthday = "01.01.1998"
+format = { DMY = { separator = "." } }
+"#;
+
+/// `config.toml` in the platform's config directory
+fn default_config_path() -> Option<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", THIS_PROGRAM_NAME)?;
+    Some(proj_dirs.config_dir().join("config.toml"))
+}
+
+/// `~/.birthday`, holding just a birthday for when nothing else gives one
+fn birthday_file_path() -> Option<PathBuf> {
+    Some(BaseDirs::new()?.home_dir().join(".birthday"))
+}
+
+/// Where to look for a config file when `--config` isn't given, most preferred first
+fn config_search_paths() -> Vec<PathBuf> {
+    let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from);
+    let current_dir = std::env::current_dir().ok();
+    config_candidates(xdg_config_home, default_config_path(), current_dir)
+}
+
+fn config_candidates(
+    xdg_config_home: Option<PathBuf>,
+    platform_path: Option<PathBuf>,
+    current_dir: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(dir) = xdg_config_home {
+        candidates.push(dir.join(THIS_PROGRAM_NAME).join("config.toml"));
+    }
+    candidates.extend(platform_path);
+    if let Some(dir) = current_dir {
+        candidates.push(dir.join(format!("{}.toml", THIS_PROGRAM_NAME)));
+    }
+    candidates.dedup();
+    candidates
+}
+
+impl ConfigFile {
+    fn from_file(path: &Path) -> Result<Self> {
+        let contents = ::std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let mut config: ConfigFile = toml::from_str(contents)?;
+        let table: toml::Table = toml::from_str(contents)?;
+        config.unknown_keys = unknown_config_keys(&table);
+        Ok(config)
+    }
+}
+
+/// Whether `err` is from reading a file that doesn't exist
+fn is_not_found(err: &color_eyre::Report) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.kind() == io::ErrorKind::NotFound)
+}
+
+/// Keys in `table` that `ConfigFile` doesn't know about, with `[[person]]` entries
+/// written as "person[0].key" and profile keys as "profiles.name.key"
+fn unknown_config_keys(table: &toml::Table) -> Vec<String> {
+    let mut unknown: Vec<String> = table
+        .keys()
+        .filter(|key| !CONFIG_KEYS.contains(&key.as_str()))
+        .cloned()
+        .collect();
+    if let Some(toml::Value::Array(people)) = table.get("person") {
+        for (i, person) in people.iter().enumerate() {
+            let Some(person) = person.as_table() else {
+                continue;
+            };
+            unknown.extend(
+                person
+                    .keys()
+                    .filter(|key| !PERSON_KEYS.contains(&key.as_str()))
+                    .map(|key| format!("person[{}].{}", i, key)),
+            );
+        }
+    }
+    if let Some(toml::Value::Table(profiles)) = table.get("profiles") {
+        for (name, profile) in profiles {
+            let Some(profile) = profile.as_table() else {
+                continue;
+            };
+            unknown.extend(
+                profile
+                    .keys()
+                    .filter(|key| !PROFILE_KEYS.contains(&key.as_str()))
+                    .map(|key| format!("profiles.{}.{}", name, key)),
+            );
+        }
+    }
+    unknown
+}
+
+/// Whole years from `birthday` to `on`, or `None` if `birthday` is later than `on`.
+/// Feb 29 birthdays are observed on Feb 28 in non-leap years
+///
+/// ```
+/// use chrono::NaiveDate;
+///
+/// let birthday = NaiveDate::from_ymd_opt(1998, 1, 1).unwrap();
+/// let on = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+/// assert_eq!(howoldami::age(birthday, on), Some(26));
+/// ```
+pub fn age(birthday: NaiveDate, on: NaiveDate) -> Option<u32> {
+    let years = on.years_since(birthday)?;
+    match milestone_date(birthday, years + 1) {
+        Some(next) if next <= on => Some(years + 1),
+        _ => Some(years),
+    }
+}
+
+/// An age split into calendar years, months, and days
+#[derive(Debug, PartialEq, Eq)]
+pub struct Breakdown {
+    pub years: u32,
+    pub months: u32,
+    pub days: u32,
+}
+
+impl Breakdown {
+    pub fn between(from: NaiveDate, to: NaiveDate) -> Option<Self> {
+        let total_months = whole_months_between(from, to)?;
+        // Step forward from the original date rather than the previous step so that
+        // month-end clamping (Jan 31 -> Feb 28) doesn't accumulate
+        let anniversary = from.checked_add_months(Months::new(total_months))?;
+        let days = (to - anniversary).num_days() as u32;
+        Some(Self {
+            years: total_months / 12,
+            months: total_months % 12,
+            days,
+        })
+    }
+
+    /// As an ISO 8601 duration like "P26Y3M14D". Zero components are left out unless
+    /// `keep_zeros`, but a zero duration is always "P0D"
+    pub fn iso_duration(&self, keep_zeros: bool) -> String {
+        let mut duration = String::from("P");
+        for (value, designator) in [(self.years, 'Y'), (self.months, 'M'), (self.days, 'D')] {
+            if keep_zeros || value != 0 {
+                duration.push_str(&format!("{}{}", value, designator));
+            }
+        }
+        if duration.len() == 1 {
+            duration.push_str("0D");
+        }
+        duration
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}, {}, {}",
+            plural(self.years, "year"),
+            plural(self.months, "month"),
+            plural(self.days, "day")
+        )
+    }
+}
+
+/// `count` followed by `noun`, adding an "s" unless there is exactly one
+fn plural(count: impl Into<i64>, noun: &str) -> String {
+    let count = count.into();
+    if count.abs() == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// The time between two arbitrary dates, in whole years and in days
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Elapsed {
+    pub years: u32,
+    pub days: i64,
+}
+
+impl Elapsed {
+    /// `None` if `from` is later than `to`
+    pub fn between(from: NaiveDate, to: NaiveDate) -> Option<Self> {
+        Some(Self {
+            years: age(from, to)?,
+            days: (to - from).num_days(),
+        })
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            plural(self.years, "year"),
+            plural(self.days, "day")
+        )
+    }
+}
+
+/// How far apart two birthdays are, in whole years and leftover days
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct AgeGap {
+    pub years: u32,
+    pub days: u32,
+    /// Whether the first birthday is the earlier one
+    pub older: bool,
+}
+
+impl AgeGap {
+    pub fn between(birthday: NaiveDate, other: NaiveDate) -> Option<Self> {
+        let (earlier, later) = (birthday.min(other), birthday.max(other));
+        // Years and days rather than the full breakdown, which would need months too
+        let months = whole_months_between(earlier, later)?;
+        let years = months / 12;
+        let anniversary = earlier.checked_add_months(Months::new(years * 12))?;
+        Some(Self {
+            years,
+            days: (later - anniversary).num_days() as u32,
+            older: birthday < other,
+        })
+    }
+}
+
+impl fmt::Display for AgeGap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.years == 0 && self.days == 0 {
+            return write!(f, "You were born on the same day");
+        }
+        write!(
+            f,
+            "You are {}, {} {}",
+            plural(self.years, "year"),
+            plural(self.days, "day"),
+            if self.older { "older" } else { "younger" }
+        )
+    }
+}
+
+/// A birthday and the age turned on it
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Anniversary {
+    pub age: u32,
+    pub date: NaiveDate,
+}
+
+/// Count the whole calendar months from `from` to `to`, borrowing from the
+/// month count when the day-of-month hasn't been reached yet
+fn whole_months_between(from: NaiveDate, to: NaiveDate) -> Option<u32> {
+    if to < from {
+        return None;
+    }
+    let mut months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    if from.checked_add_months(Months::new(months as u32))? > to {
+        months -= 1;
+    }
+    Some(months as u32)
+}
+
+/// Move a date whose year is numbered historically, where 1 BCE is -1 and year 0 doesn't
+/// exist, to chrono's astronomical numbering, where 1 BCE is year 0
+fn historical_to_astronomical(date: NaiveDate) -> Result<NaiveDate> {
+    match date.year() {
+        0 => bail!("There is no year 0 in historical numbering. 1 BCE is year -1"),
+        year if year < 0 => {
+            anniversary(date, year + 1).ok_or_else(|| eyre!("Invalid date: {}", date))
+        }
+        _ => Ok(date),
+    }
+}
+
+/// The date `date`'s month and day fall on in `year`. Feb 29 is observed on
+/// Feb 28 in non-leap years
+fn anniversary(date: NaiveDate, year: i32) -> Option<NaiveDate> {
+    date.with_year(year)
+        .or_else(|| NaiveDate::from_ymd_opt(year, date.month(), 28))
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// A source for the current time, so that anything depending on today's date can be tested
+pub trait Clock: fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Today's date in the local timezone
+    fn today(&self) -> NaiveDate {
+        wall_clock(self.now(), &Local).date()
+    }
+}
+
+/// The real clock
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at a single instant
+#[derive(Debug)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// The date and time on a wall clock in `tz` at `instant`. An instant has exactly one
+/// wall clock time even during DST transitions, so unlike [`resolve_local`] there is
+/// nothing to choose between
+fn wall_clock<Tz: TimeZone>(instant: DateTime<Utc>, tz: &Tz) -> NaiveDateTime {
+    instant.with_timezone(tz).naive_local()
+}
+
+/// The instant a wall clock in `tz` shows `local`. Clocks set back show some times
+/// twice, and the earlier, pre-transition instant is chosen. Clocks set forward skip
+/// some times, which are read with the offset from before the jump, landing the same
+/// distance past it. So a skipped midnight (Santiago, September) is 01:00 the same day
+fn resolve_local<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Tz>> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(instant) => Some(instant),
+        LocalResult::Ambiguous(earlier, _) => Some(earlier),
+        LocalResult::None => {
+            // No zone has a gap longer than a day, so a day earlier is before it
+            let before = tz
+                .from_local_datetime(&(local - chrono::Duration::days(1)))
+                .earliest()?;
+            let utc = local - before.offset().fix();
+            Some(tz.from_utc_datetime(&utc))
+        }
+    }
+}
+
+/// Gregorian leap years: every fourth year, except centuries not divisible by 400
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = first.checked_add_months(Months::new(1))?;
+    Some((next - first).num_days() as u32)
+}
+
+/// The half-birthday six months after `birthday`'s month and day, in `year`. Days
+/// that don't exist in the half-birthday month (Aug 31 -> Feb 31) are clamped to
+/// the end of the month
+fn half_birthday_in(birthday: NaiveDate, year: i32) -> Option<NaiveDate> {
+    let month = (birthday.month0() + 6) % 12 + 1;
+    let day = birthday.day().min(days_in_month(year, month)?);
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+/// The next half-birthday on or after `on`
+fn next_half_birthday(birthday: NaiveDate, on: NaiveDate) -> Option<NaiveDate> {
+    let this_year = half_birthday_in(birthday, on.year())?;
+    if this_year >= on {
+        Some(this_year)
+    } else {
+        half_birthday_in(birthday, on.year() + 1)
+    }
+}
+
+/// The next occurrence of `birthday`'s month and day on or after `on`
+fn next_birthday(birthday: NaiveDate, on: NaiveDate) -> Option<NaiveDate> {
+    let this_year = anniversary(birthday, on.year())?;
+    if this_year >= on {
+        Some(this_year)
+    } else {
+        anniversary(birthday, on.year() + 1)
+    }
+}
+
+/// Days from `on` to the nearest occurrence of `birthday`'s month and day, negative
+/// when the nearest one has passed
+fn days_to_nearest_birthday(birthday: NaiveDate, on: NaiveDate) -> Option<i64> {
+    [on.year() - 1, on.year(), on.year() + 1]
+        .into_iter()
+        .filter_map(|year| anniversary(birthday, year))
+        .map(|date| (date - on).num_days())
+        .min_by_key(|days| days.abs())
+}
+
+/// The date someone born on `birthday` turns `age`
+fn milestone_date(birthday: NaiveDate, age: u32) -> Option<NaiveDate> {
+    anniversary(birthday, birthday.year().checked_add(age.try_into().ok()?)?)
+}
+
+/// Every birthday from `start` to `end` inclusive, observing Feb 29 on Feb 28 in
+/// non-leap years. The day of birth itself doesn't count
+fn anniversaries_between(
+    birthday: NaiveDate,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<Anniversary> {
+    let first = (start.year() - birthday.year()).max(1);
+    let last = end.year() - birthday.year();
+    (first..=last)
+        .filter_map(|age| {
+            let age = age.try_into().ok()?;
+            Some(Anniversary {
+                age,
+                date: milestone_date(birthday, age)?,
+            })
+        })
+        .filter(|anniversary| (start..=end).contains(&anniversary.date))
+        .collect()
+}
+
+/// How many times `birthday`'s month and day have come around after it, up to and
+/// including `on`
+fn birthdays_celebrated(birthday: NaiveDate, on: NaiveDate) -> Option<u32> {
+    age(birthday, on)
+}
+
+/// The first multiple of `step` after `age`
+fn next_milestone_age(age: u32, step: u32) -> u32 {
+    (age / step + 1) * step
+}
+
+/// The whole percentage of the way from the last birthday on or before `on` to the
+/// next one, and the age turned at the next one. On a birthday itself this is 0%,
+/// the start of the new year of age
+fn year_progress(birthday: NaiveDate, on: NaiveDate) -> Option<(u32, u32)> {
+    let years = age(birthday, on)?;
+    let last = milestone_date(birthday, years)?;
+    let next = milestone_date(birthday, years + 1)?;
+    let percent = (on - last).num_days() * 100 / (next - last).num_days();
+    Some((percent as u32, years + 1))
+}
+
+/// The oldest round age listed when no milestone ages are given
+const LAST_ROUND_MILESTONE: u32 = 100;
+
+/// A report for one member of a household
+#[derive(Debug, Serialize)]
+pub struct PersonReport {
+    pub name: String,
+    #[serde(flatten)]
+    pub report: Report,
+}
+
+/// The result of an age calculation, ready to be printed or serialized
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Negative when the birthday is after the current date
+    pub age: i64,
+    /// Omitted for the default unit to keep the output stable
+    #[serde(skip_serializing_if = "Unit::is_years")]
+    pub unit: Unit,
+    /// Days past the last whole week, for the weeks-days unit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remainder_days: Option<i64>,
+    pub birthday: NaiveDate,
+    pub current_date: NaiveDate,
+    /// Informational messages for the user, kept out of machine-readable output
+    #[serde(skip)]
+    pub messages: Vec<String>,
+    /// Additional lines printed after the age
+    #[serde(skip)]
+    pub extras: Vec<String>,
+}
+
+impl Report {
+    /// The age as printed in plain output
+    pub fn age_string(&self) -> String {
+        match self.remainder_days {
+            Some(days) => format!("{} {}", plural(self.age, "week"), plural(days, "day")),
+            None => self.age.to_string(),
+        }
+    }
+
+    /// The age as an ISO 8601 duration, negated with a leading "-" for a birthday in
+    /// the future
+    pub fn iso_duration(&self, keep_zeros: bool) -> String {
+        match Breakdown::between(self.birthday, self.current_date) {
+            Some(breakdown) => breakdown.iso_duration(keep_zeros),
+            None => Breakdown::between(self.current_date, self.birthday)
+                .map(|breakdown| format!("-{}", breakdown.iso_duration(keep_zeros)))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The age, birthday, and current date separated by tabs, for `--porcelain`
+    pub fn porcelain(&self) -> String {
+        format!("{}\t{}\t{}", self.age, self.birthday, self.current_date)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Just the number
+    #[default]
+    Plain,
+    /// A JSON object with the age and the dates used to compute it
+    Json,
+    /// Like json, but lists print one object per line instead of an array
+    Jsonl,
+    /// An ISO 8601 duration of years, months, and days, like P26Y3M14D
+    IsoDuration,
+}
+
+/// How human years convert to dog years
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DogYearFormula {
+    /// Seven dog years to every human year
+    #[default]
+    Simple,
+    /// 10.5 dog years for each of the first two human years, then 4 for each after
+    Accurate,
+}
+
+impl DogYearFormula {
+    pub fn dog_years(self, years: u32) -> u32 {
+        match self {
+            DogYearFormula::Simple => years * 7,
+            DogYearFormula::Accurate if years <= 2 => years * 21 / 2,
+            DogYearFormula::Accurate => 21 + (years - 2) * 4,
+        }
+    }
+}
+
+/// The calendar a birthday is written in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Calendar {
+    #[default]
+    Gregorian,
+    /// The Julian calendar, for dates before a country adopted the Gregorian one.
+    /// Julian-only leap days like Feb 29, 1700 can't be entered
+    Julian,
+}
+
+impl Calendar {
+    /// The proleptic Gregorian date for `date`'s year, month, and day read in this
+    /// calendar
+    pub fn to_gregorian(self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Calendar::Gregorian => Some(date),
+            Calendar::Julian => julian_to_gregorian(date.year(), date.month(), date.day()),
+        }
+    }
+}
+
+/// Convert a Julian calendar date through its Julian Day Number. The calendars
+/// drift apart by a day in every century year that isn't divisible by 400, since
+/// only the Julian calendar makes those leap years: 10 days in 1582, 11 from
+/// March 1700, 13 today
+fn julian_to_gregorian(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    // Count from March so the leap day falls at the end of the year
+    let a = (14 - month as i64) / 12;
+    let y = year as i64 + 4800 - a;
+    let m = month as i64 + 12 * a - 3;
+    let jdn = day as i64 + (153 * m + 2) / 5 + 365 * y + y / 4 - 32083;
+    // 1721426 is the Julian Day Number of Jan 1, year 1 in the proleptic Gregorian
+    // calendar, which chrono numbers day 1
+    NaiveDate::from_num_days_from_ce_opt((jdn - 1721425).try_into().ok()?)
+}
+
+/// The day a calendar week starts on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WeekStart {
+    Sunday,
+    Monday,
+}
+
+impl WeekStart {
+    /// Days from the start of the week to `weekday`
+    fn days_into_week(self, weekday: Weekday) -> i64 {
+        match self {
+            WeekStart::Sunday => weekday.num_days_from_sunday().into(),
+            WeekStart::Monday => weekday.num_days_from_monday().into(),
+        }
+    }
+}
+
+/// When to style output with color
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only when standard output is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stdout().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Bold `age` when `color` is set
+pub fn style_age(age: &str, color: bool) -> String {
+    if color {
+        age.bold().to_string()
+    } else {
+        age.to_owned()
+    }
+}
+
+/// The unit the age is reported in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    /// Counted from the time of birth, or midnight if none was given
+    Seconds,
+    Hours,
+    Days,
+    Weeks,
+    /// Whole weeks plus the days left over, like "8 weeks 3 days"
+    #[serde(rename = "weeks-days")]
+    WeeksDays,
+    Months,
+    #[default]
+    Years,
+}
+
+impl Unit {
+    fn is_years(&self) -> bool {
+        *self == Unit::Years
+    }
+}
+
+/// How fractional results are cut down to the printed precision
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Rounding {
+    /// Round down, matching how whole years are counted
+    #[default]
+    Floor,
+    Ceil,
+    Nearest,
+}
+
+impl Rounding {
+    /// Print `value` with `precision` decimal places, rounded this way
+    pub fn format(self, value: f64, precision: usize) -> String {
+        let scale = 10f64.powi(precision as i32);
+        let scaled = value * scale;
+        let rounded = match self {
+            Rounding::Floor => scaled.floor(),
+            Rounding::Ceil => scaled.ceil(),
+            Rounding::Nearest => scaled.round(),
+        };
+        format!("{:.*}", precision, rounded / scale)
+    }
+}
+
+const DAYS_PER_YEAR: f64 = 365.25;
+
+const DEFAULT_GREETING: &str = "Happy birthday!";
+
+#[derive(Debug, Default)]
+pub struct App {
+    birthday: NaiveDate,
+    /// Midnight when no birth time was given
+    birth_time: NaiveTime,
+    current_date: NaiveDate,
+    current_time: NaiveTime,
+    verbosity: Verbosity,
+    wish_happy_birthday: bool,
+    /// Whether the birthday has a month and day, rather than only a year or month
+    full_birthday: bool,
+    countdown: bool,
+    weekday: bool,
+    unit: Unit,
+    zodiac: Option<Zodiac>,
+    /// Replaces the birthday greeting. `{age}` and `{name}` are expanded
+    greeting: Option<String>,
+    /// Who this app is for, when listing a household
+    name: Option<String>,
+    sleeps: bool,
+    half_birthday: bool,
+    born_on_leap_year: bool,
+    /// Ages to list upcoming birthdays for. Empty means every round decade
+    milestones: Option<Vec<u32>>,
+    year_progress: bool,
+    /// Whether to style output with ANSI escape codes
+    color: bool,
+    /// Step between milestone ages when finding the next one
+    next_milestone: Option<u32>,
+    dog_years: Option<DogYearFormula>,
+    count_birthdays: bool,
+    /// Where the birth and current times are wall clock times, if known
+    timezone: Option<chrono_tz::Tz>,
+    /// Days either side of the birthday to mention it on
+    birthday_window: u32,
+    /// Someone to compare ages with
+    other_birthday: Option<NaiveDate>,
+    /// Years to measure the age against as a share of a lifetime
+    life_expectancy: Option<u32>,
+    /// Age to count down to retirement at
+    retire_at: Option<u32>,
+    /// The day calendar weeks start on. `None` starts each week on the birth weekday
+    week_start: Option<WeekStart>,
+    /// Beats per minute to estimate lifetime heartbeats with
+    heartbeats: Option<u32>,
+    /// chrono format string for printed dates. `None` prints ISO 8601
+    output_date_format: Option<String>,
+    /// Range to list birthdays in
+    birthdays_between: Option<(NaiveDate, NaiveDate)>,
+}
+
+impl App {
+    pub fn calculate(&self) -> Report {
+        let current_date = self.current_date;
+        let birthday = self.birthday;
+        let mut messages = Vec::new();
+        let years = self.age_in(Unit::Years);
+
+        if self.verbosity >= Verbosity::Verbose && years >= 0 {
+            messages.push(format!(
+                "You are {} old (born {}, as of {}).",
+                plural(years, "year"),
+                self.show_date(birthday),
+                self.show_date(current_date)
+            ));
+        }
+
+        // Nobody has a birthday to celebrate before they are born
+        if self.wish_happy_birthday && years >= 0 && self.verbosity >= Verbosity::Normal {
+            if anniversary(birthday, current_date.year()) == Some(current_date) {
+                let greeting = self.greeting(years);
+                if self.color {
+                    messages.push(greeting.bright_magenta().to_string());
+                } else {
+                    messages.push(greeting);
+                }
+            } else if let Some(message) = self.near_birthday_message() {
+                messages.push(message);
+            }
+        }
+
+        if years < 0 && self.verbosity >= Verbosity::Normal {
+            messages.push(format!("Born {} in the future", plural(-years, "year")));
+        }
+        let age = self.age_in(self.unit);
+
+        let mut extras = Vec::new();
+        if self.countdown {
+            extras.push(self.countdown_message());
+        }
+        if self.sleeps {
+            extras.push(self.sleeps_message());
+        }
+        if self.half_birthday {
+            extras.push(self.half_birthday_message());
+        }
+        if self.year_progress {
+            extras.push(self.year_progress_message());
+        }
+        if let Some(expectancy) = self.life_expectancy {
+            if years >= 0 && self.verbosity >= Verbosity::Normal {
+                extras.push(self.life_expectancy_message(expectancy));
+            }
+        }
+        if self.count_birthdays {
+            extras.push(match birthdays_celebrated(birthday, current_date) {
+                Some(count) => format!("You have had {}.", plural(count, "birthday")),
+                None => "You haven't had a birthday yet.".to_owned(),
+            });
+        }
+        if let Some(ages) = &self.milestones {
+            extras.extend(self.milestone_messages(ages));
+        }
+        if let Some(bpm) = self.heartbeats {
+            if years >= 0 && self.verbosity >= Verbosity::Normal {
+                extras.push(match self.heartbeats(bpm) {
+                    Some(beats) => format!("Your heart has beaten about {} times.", beats),
+                    None => "Your heart has beaten too many times to count.".to_owned(),
+                });
+            }
+        }
+        if let Some(retire_at) = self.retire_at {
+            extras.push(self.retirement_message(retire_at));
+        }
+        if let Some(step) = self.next_milestone {
+            extras.push(self.next_milestone_message(step));
+        }
+        if let Some(formula) = self.dog_years {
+            if years >= 0 && self.verbosity >= Verbosity::Normal {
+                extras.push(format!(
+                    "In dog years, you are {}.",
+                    formula.dog_years(years as u32)
+                ));
+            }
+        }
+        if self.weekday && self.verbosity >= Verbosity::Normal {
+            extras.push(format!(
+                "You were born on a {}.",
+                weekday_name(birthday.weekday())
+            ));
+        }
+        if self.born_on_leap_year
+            && self.verbosity >= Verbosity::Normal
+            && is_leap_year(birthday.year())
+        {
+            extras.push("You were born in a leap year.".to_owned());
+        }
+        if let Some(zodiac) = self.zodiac {
+            if self.verbosity >= Verbosity::Normal {
+                extras.extend(self.zodiac_messages(zodiac));
+            }
+        }
+
+        let remainder_days = (self.unit == Unit::WeeksDays).then(|| self.age_in(Unit::Days) % 7);
+
+        Report {
+            age,
+            unit: self.unit,
+            remainder_days,
+            birthday,
+            current_date,
+            messages,
+            extras,
+        }
+    }
+
+    pub fn age_in(&self, unit: Unit) -> i64 {
+        let days = (self.current_date - self.birthday).num_days();
+        // NaiveDateTime spans under 300,000 years either side of 0 CE, so even the
+        // difference in seconds is far from overflowing an i64
+        let born = NaiveDateTime::new(self.birthday, self.birth_time);
+        let now = NaiveDateTime::new(self.current_date, self.current_time);
+        // In a known timezone, count the hours clocks skipped or repeated for DST
+        let elapsed = self
+            .timezone
+            .and_then(|tz| Some(resolve_local(&tz, now)? - resolve_local(&tz, born)?))
+            .unwrap_or_else(|| now.signed_duration_since(born));
+        match unit {
+            Unit::Seconds => elapsed.num_seconds(),
+            Unit::Hours => elapsed.num_hours(),
+            Unit::Days => days,
+            Unit::Weeks | Unit::WeeksDays => days / 7,
+            Unit::Months => match whole_months_between(self.birthday, self.current_date) {
+                Some(months) => months.into(),
+                None => {
+                    let months = whole_months_between(self.current_date, self.birthday);
+                    -i64::from(months.unwrap_or_default())
+                }
+            },
+            Unit::Years => match age(self.birthday, self.current_date) {
+                Some(years) => years.into(),
+                None => {
+                    let years = age(self.current_date, self.birthday);
+                    -i64::from(years.unwrap_or_default())
+                }
+            },
+        }
+    }
+
+    /// Lines describing the birthday under `zodiac`
+    pub fn zodiac_messages(&self, zodiac: Zodiac) -> Vec<String> {
+        match zodiac {
+            Zodiac::Chinese => {
+                let year = self.birthday.year();
+                let mut messages = vec![format!(
+                    "Your Chinese zodiac animal is the {}.",
+                    zodiac::chinese_animal(year)
+                )];
+                if self.verbosity >= Verbosity::Verbose
+                    && zodiac::may_precede_chinese_new_year(self.birthday)
+                {
+                    messages.push(format!(
+                        "If you were born before Chinese New Year in {}, you were born in the year of the {} instead.",
+                        year,
+                        zodiac::chinese_animal(year - 1)
+                    ));
+                }
+                messages
+            }
+            Zodiac::Western => vec![format!(
+                "Your star sign is {}.",
+                zodiac::western_sign(self.birthday)
+            )],
+        }
+    }
+
+    fn greeting(&self, age: i64) -> String {
+        let Some(template) = &self.greeting else {
+            return DEFAULT_GREETING.to_owned();
+        };
+        template
+            .replace("{age}", &age.to_string())
+            .replace("{name}", self.name.as_deref().unwrap_or("you"))
+    }
+
+    /// The greeting if the current date is the birthday, observing Feb 29 on Feb 28 in
+    /// non-leap years. Unlike the greeting in [`Self::calculate`], this is given on the
+    /// real clock's date too, for `--notify-on-birthday`
+    pub fn birthday_greeting(&self) -> Option<String> {
+        let today = anniversary(self.birthday, self.current_date.year())? == self.current_date;
+        (self.full_birthday && today && self.birthday <= self.current_date)
+            .then(|| self.greeting(self.age_in(Unit::Years)))
+    }
+
+    /// `date` in the `--output-date-format`
+    fn show_date(&self, date: NaiveDate) -> String {
+        match &self.output_date_format {
+            Some(format) => date.format(format).to_string(),
+            None => date.to_string(),
+        }
+    }
+
+    fn half_birthday_message(&self) -> String {
+        match next_half_birthday(self.birthday, self.current_date) {
+            Some(next) if next == self.current_date => "Today is your half-birthday!".to_owned(),
+            Some(next) => format!(
+                "Your next half-birthday is {}, {} from now",
+                self.show_date(next),
+                plural((next - self.current_date).num_days(), "day")
+            ),
+            None => "Could not determine your next half-birthday".to_owned(),
+        }
+    }
+
+    fn sleeps_message(&self) -> String {
+        match next_birthday(self.birthday, self.current_date) {
+            Some(next) if next == self.current_date => "Happy birthday! 0 sleeps!".to_owned(),
+            // Each night between now and then is one sleep
+            Some(next) => format!(
+                "{} until your birthday!",
+                plural((next - self.current_date).num_days(), "sleep")
+            ),
+            None => "Could not determine your next birthday".to_owned(),
+        }
+    }
+
+    /// One line per milestone still ahead of the current date
+    fn milestone_messages(&self, ages: &[u32]) -> Vec<String> {
+        let round: Vec<u32> = (10..=LAST_ROUND_MILESTONE).step_by(10).collect();
+        let ages = if ages.is_empty() { &round } else { ages };
+        ages.iter()
+            .filter_map(|&age| Some((age, milestone_date(self.birthday, age)?)))
+            .filter(|(_, date)| *date >= self.current_date)
+            .map(|(age, date)| format!("You turn {} on {}.", age, self.show_date(date)))
+            .collect()
+    }
+
+    fn retirement_message(&self, retire_at: u32) -> String {
+        let Some(date) = milestone_date(self.birthday, retire_at) else {
+            return "Could not determine your retirement date".to_owned();
+        };
+        if date == self.current_date {
+            return "You can retire today!".to_owned();
+        }
+        if let Some(remaining) = Elapsed::between(self.current_date, date) {
+            return format!(
+                "You can retire on {}, in {}",
+                self.show_date(date),
+                remaining
+            );
+        }
+        match age(date, self.current_date) {
+            Some(0) | None => format!(
+                "You could have retired {} ago.",
+                plural((self.current_date - date).num_days(), "day")
+            ),
+            Some(years) => format!("You could have retired {} ago.", plural(years, "year")),
+        }
+    }
+
+    fn year_progress_message(&self) -> String {
+        match year_progress(self.birthday, self.current_date) {
+            Some((percent, next_age)) => format!("{}% of the way to {}", percent, next_age),
+            None => "Could not determine your progress to your next birthday".to_owned(),
+        }
+    }
+
+    fn life_expectancy_message(&self, expectancy: u32) -> String {
+        let percent = self.decimal_age() / f64::from(expectancy) * 100.0;
+        let lifetime = plural(expectancy, "year");
+        if percent > 100.0 {
+            format!(
+                "You have lived 100% of a life expectancy of {}, and kept going",
+                lifetime
+            )
+        } else {
+            format!(
+                "You have lived {:.1}% of a life expectancy of {}",
+                percent, lifetime
+            )
+        }
+    }
+
+    fn next_milestone_message(&self, step: u32) -> String {
+        let next = age(self.birthday, self.current_date)
+            .map(|years| next_milestone_age(years, step))
+            .and_then(|next| Some((next, milestone_date(self.birthday, next)?)));
+        match next {
+            Some((next, date)) => format!(
+                "You turn {} on {}, {} from now",
+                next,
+                self.show_date(date),
+                plural((date - self.current_date).num_days(), "day")
+            ),
+            None => "Could not determine your next milestone".to_owned(),
+        }
+    }
+
+    /// Mention a birthday that is not today but within the birthday window
+    fn near_birthday_message(&self) -> Option<String> {
+        let days = days_to_nearest_birthday(self.birthday, self.current_date)?;
+        if days == 0 || days.unsigned_abs() > u64::from(self.birthday_window) {
+            return None;
+        }
+        Some(if days > 0 {
+            format!("Your birthday is in {}!", plural(days, "day"))
+        } else {
+            format!("Your birthday was {} ago!", plural(-days, "day"))
+        })
+    }
+
+    /// How long until the next birthday, or a greeting if it is today
+    pub fn countdown_message(&self) -> String {
+        if self.birthday > self.current_date {
+            return format!(
+                "{} until you are born",
+                plural((self.birthday - self.current_date).num_days(), "day")
+            );
+        }
+        match next_birthday(self.birthday, self.current_date) {
+            Some(next) if next == self.current_date => "Today is your birthday!".to_owned(),
+            Some(next) => format!(
+                "{} until your next birthday",
+                plural((next - self.current_date).num_days(), "day")
+            ),
+            None => "Could not determine your next birthday".to_owned(),
+        }
+    }
+
+    /// The time since the birthday, for when it is an arbitrary `--since` date
+    pub fn elapsed(&self) -> Option<Elapsed> {
+        Elapsed::between(self.birthday, self.current_date)
+    }
+
+    /// How much older or younger than `--other-birthday` the birthday makes you
+    pub fn age_gap(&self) -> Option<AgeGap> {
+        AgeGap::between(self.birthday, self.other_birthday?)
+    }
+
+    pub fn breakdown(&self) -> Option<Breakdown> {
+        Breakdown::between(self.birthday, self.current_date)
+    }
+
+    /// Fill in `{age}`, `{years}`, `{days}`, `{birthday}`, and `{today}` in `template`
+    pub fn render_template(&self, template: &str) -> Result<String> {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                bail!("Unclosed placeholder in template '{}'", template);
+            };
+            let placeholder = &rest[start + 1..start + end];
+            let value = match placeholder {
+                "age" => self.age_in(self.unit).to_string(),
+                "years" => self.age_in(Unit::Years).to_string(),
+                "days" => self.age_in(Unit::Days).to_string(),
+                "birthday" => self.show_date(self.birthday),
+                "today" => self.show_date(self.current_date),
+                _ => bail!("Unknown placeholder {{{}}} in template", placeholder),
+            };
+            rendered.push_str(&value);
+            rest = &rest[start + end + 1..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+
+    /// Age in fractional years, averaging leap years into a 365.25 day year
+    pub fn decimal_age(&self) -> f64 {
+        (self.current_date - self.birthday).num_days() as f64 / DAYS_PER_YEAR
+    }
+
+    /// Days from the birthday to the current date
+    pub fn days_alive(&self) -> i64 {
+        (self.current_date - self.birthday).num_days()
+    }
+
+    /// The birthdays in the `--birthdays-between` range
+    pub fn birthdays_between(&self) -> Option<Vec<Anniversary>> {
+        let (start, end) = self.birthdays_between?;
+        Some(anniversaries_between(self.birthday, start, end))
+    }
+
+    /// A birthday as "You turn 30 on 2020-01-01"
+    pub fn anniversary_message(&self, anniversary: &Anniversary) -> String {
+        format!(
+            "You turn {} on {}",
+            anniversary.age,
+            self.show_date(anniversary.date)
+        )
+    }
+
+    /// The most recent birthday on or before the current date, and the age turned on it
+    pub fn last_birthday(&self) -> Option<(u32, NaiveDate)> {
+        let years = age(self.birthday, self.current_date)?;
+        Some((years, milestone_date(self.birthday, years)?))
+    }
+
+    /// The last birthday as "34, on 2024-01-15"
+    pub fn last_birthday_string(&self) -> Option<String> {
+        let (years, date) = self.last_birthday()?;
+        Some(format!("{}, on {}", years, self.show_date(date)))
+    }
+
+    /// Which day of the current year, from 1 to 366, this year's birthday falls on
+    pub fn birthday_day_of_year(&self) -> Option<u32> {
+        Some(anniversary(self.birthday, self.current_date.year())?.ordinal())
+    }
+
+    /// The age reached on this calendar year's birthday, whether or not it has passed
+    pub fn turning(&self) -> i64 {
+        i64::from(self.current_date.year()) - i64::from(self.birthday.year())
+    }
+
+    /// Minutes alive, from the birth time if known, times `bpm`. `None` on overflow
+    pub fn heartbeats(&self, bpm: u32) -> Option<i64> {
+        let born = NaiveDateTime::new(self.birthday, self.birth_time);
+        let now = NaiveDateTime::new(self.current_date, self.current_time);
+        now.signed_duration_since(born)
+            .num_minutes()
+            .checked_mul(bpm.into())
+    }
+
+    /// Which week of life the current date falls in, counting the birth week as week 1.
+    /// With a week start, later weeks begin on that day rather than the birth weekday
+    pub fn week_of_life(&self) -> i64 {
+        let offset = self
+            .week_start
+            .map_or(0, |start| start.days_into_week(self.birthday.weekday()));
+        (self.days_alive() + offset).div_euclid(7) + 1
+    }
+
+    /// The dates as they were understood, in ISO 8601, to catch swapped months and days
+    pub fn parsed_dates(&self) -> Vec<String> {
+        vec![
+            format!("Parsed birthday: {}", self.birthday),
+            format!("Parsed current date: {}", self.current_date),
+        ]
+    }
+}
+
+/// One line of a `--batch` file
+#[derive(Debug)]
+pub struct BatchLine {
+    /// Counting from 1
+    pub line_number: usize,
+    pub input: String,
+    pub app: Result<App>,
+}
+
+impl BatchLine {
+    /// The line's error as a JSON object like [`error_json`], plus "line" and "input"
+    /// fields. `None` if the line parsed
+    pub fn error_json(&self) -> Option<String> {
+        let mut value = error_value(self.app.as_ref().err()?);
+        value["line"] = self.line_number.into();
+        value["input"] = self.input.clone().into();
+        Some(value.to_string())
+    }
+}
+
+#[derive(Debug)]
+struct Person {
+    name: String,
+    birthday: Option<DateSpecifier>,
+}
+
+/// Where a setting's final value came from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    #[default]
+    Default,
+    BirthdayFile,
+    File,
+    Env,
+    Args,
+    Stdin,
+    Prompt,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Layer::Default => "the default",
+            Layer::BirthdayFile => "~/.birthday",
+            Layer::File => "the config file",
+            Layer::Env => "the environment",
+            Layer::Args => "the command line",
+            Layer::Stdin => "stdin",
+            Layer::Prompt => "the prompt",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The layer that last set each of the settings `--explain` reports on
+#[derive(Debug, Default)]
+struct Provenance {
+    birthday: Layer,
+    current_date: Layer,
+    format: Layer,
+}
+
+#[derive(Debug)]
+pub struct LayeredAppConfigBuilder {
+    birthday: Option<DateSpecifier>,
+    people: Vec<Person>,
+    current_date: Option<DateSpecifier>,
+    format: DateFormat,
+    verbosity: Verbosity,
+    countdown: bool,
+    weekday: bool,
+    unit: Unit,
+    zodiac: Option<Zodiac>,
+    century_pivot: u32,
+    greeting: Option<String>,
+    allow_future: bool,
+    sleeps: bool,
+    utc: bool,
+    /// Where "now" comes from when no current date is given
+    clock: Box<dyn Clock>,
+    /// Where `stack_config_layer` looks for `~/.birthday`
+    birthday_file: Option<PathBuf>,
+    half_birthday: bool,
+    born_on_leap_year: bool,
+    milestones: Option<Vec<u32>>,
+    year_progress: bool,
+    color: bool,
+    timezone: Option<chrono_tz::Tz>,
+    next_milestone: Option<u32>,
+    dog_years: Option<DogYearFormula>,
+    count_birthdays: bool,
+    birthday_window: u32,
+    calendar: Calendar,
+    other_birthday: Option<String>,
+    profile: Option<String>,
+    /// Whether a config file had the selected profile
+    profile_found: bool,
+    life_expectancy: Option<u32>,
+    /// For `--life-expectancy` without a value
+    default_life_expectancy: u32,
+    retire_at: Option<u32>,
+    week_start: Option<WeekStart>,
+    historical_year_math: bool,
+    heartbeats: Option<u32>,
+    output_date_format: Option<String>,
+    birthdays_between: Vec<String>,
+    strict: bool,
+    strict_config: bool,
+    clamp_years: bool,
+    /// For `--format detect`, infer the format from the birthday once every layer has
+    /// stacked, so a birthday from stdin or the prompt is detected too
+    detect_format: bool,
+    /// Whether the birthday is an arbitrary `--since` date
+    since: bool,
+    provenance: Provenance,
+}
+
+impl Default for LayeredAppConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayeredAppConfigBuilder {
+    pub fn new() -> Self {
+        Self {
+            birthday: None,
+            people: Vec::new(),
+            current_date: None,
+            format: DateFormat::default(),
+            verbosity: Verbosity::Normal,
+            countdown: false,
+            weekday: false,
+            unit: Unit::default(),
+            zodiac: None,
+            century_pivot: DEFAULT_CENTURY_PIVOT,
+            greeting: None,
+            allow_future: false,
+            sleeps: false,
+            utc: false,
+            clock: Box::new(SystemClock),
+            birthday_file: birthday_file_path(),
+            half_birthday: false,
+            born_on_leap_year: false,
+            milestones: None,
+            year_progress: false,
+            color: false,
+            timezone: None,
+            next_milestone: None,
+            dog_years: None,
+            count_birthdays: false,
+            birthday_window: 0,
+            calendar: Calendar::default(),
+            other_birthday: None,
+            profile: None,
+            profile_found: false,
+            life_expectancy: None,
+            default_life_expectancy: DEFAULT_LIFE_EXPECTANCY,
+            retire_at: None,
+            week_start: None,
+            historical_year_math: false,
+            heartbeats: None,
+            output_date_format: None,
+            birthdays_between: Vec::new(),
+            strict: false,
+            strict_config: false,
+            clamp_years: false,
+            detect_format: false,
+            since: false,
+            provenance: Provenance::default(),
+        }
+    }
+
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    pub fn countdown(mut self, countdown: bool) -> Self {
+        self.countdown = countdown;
+        self
+    }
+
+    pub fn weekday(mut self, weekday: bool) -> Self {
+        self.weekday = weekday;
+        self
+    }
+
+    pub fn unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn zodiac(mut self, zodiac: Option<Zodiac>) -> Self {
+        self.zodiac = zodiac;
+        self
+    }
+
+    pub fn century_pivot(mut self, century_pivot: u32) -> Self {
+        self.century_pivot = century_pivot;
+        self
+    }
+
+    pub fn allow_future(mut self, allow_future: bool) -> Self {
+        self.allow_future = allow_future;
+        self
+    }
+
+    pub fn sleeps(mut self, sleeps: bool) -> Self {
+        self.sleeps = sleeps;
+        self
+    }
+
+    pub fn utc(mut self, utc: bool) -> Self {
+        self.utc = utc;
+        self
+    }
+
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Read the birthday file from `path` instead of `~/.birthday`, or skip it for `None`
+    pub fn birthday_file(mut self, path: Option<PathBuf>) -> Self {
+        self.birthday_file = path;
+        self
+    }
+
+    pub fn half_birthday(mut self, half_birthday: bool) -> Self {
+        self.half_birthday = half_birthday;
+        self
+    }
+
+    pub fn born_on_leap_year(mut self, born_on_leap_year: bool) -> Self {
+        self.born_on_leap_year = born_on_leap_year;
+        self
+    }
+
+    pub fn milestones(mut self, milestones: Option<Vec<u32>>) -> Self {
+        self.milestones = milestones;
+        self
+    }
+
+    pub fn year_progress(mut self, year_progress: bool) -> Self {
+        self.year_progress = year_progress;
+        self
+    }
+
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn timezone(mut self, timezone: Option<chrono_tz::Tz>) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn next_milestone(mut self, next_milestone: Option<u32>) -> Self {
+        self.next_milestone = next_milestone;
+        self
+    }
+
+    pub fn dog_years(mut self, dog_years: Option<DogYearFormula>) -> Self {
+        self.dog_years = dog_years;
+        self
+    }
+
+    pub fn count_birthdays(mut self, count_birthdays: bool) -> Self {
+        self.count_birthdays = count_birthdays;
+        self
+    }
+
+    pub fn birthday_window(mut self, birthday_window: u32) -> Self {
+        self.birthday_window = birthday_window;
+        self
+    }
+
+    pub fn calendar(mut self, calendar: Calendar) -> Self {
+        self.calendar = calendar;
+        self
+    }
+
+    pub fn other_birthday(mut self, other_birthday: Option<String>) -> Self {
+        self.other_birthday = other_birthday;
+        self
+    }
+
+    /// The START and END of `--birthdays-between`, or nothing to not list birthdays
+    pub fn birthdays_between(mut self, birthdays_between: Vec<String>) -> Self {
+        self.birthdays_between = birthdays_between;
+        self
+    }
+
+    pub fn profile(mut self, profile: Option<String>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn retire_at(mut self, retire_at: Option<u32>) -> Self {
+        self.retire_at = retire_at;
+        self
+    }
+
+    pub fn week_start(mut self, week_start: Option<WeekStart>) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    pub fn historical_year_math(mut self, historical_year_math: bool) -> Self {
+        self.historical_year_math = historical_year_math;
+        self
+    }
+
+    pub fn heartbeats(mut self, heartbeats: Option<u32>) -> Self {
+        self.heartbeats = heartbeats;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn strict_config(mut self, strict_config: bool) -> Self {
+        self.strict_config = strict_config;
+        self
+    }
+
+    pub fn clamp_years(mut self, clamp_years: bool) -> Self {
+        self.clamp_years = clamp_years;
+        self
+    }
+
+    pub fn stack_args_layer(mut self, args: &Args) -> Result<Self> {
+        if args.porcelain {
+            self.verbosity = Verbosity::Quiet;
+        } else if args.verbose > 0 {
+            self.verbosity = Verbosity::from_count(args.verbose);
+        } else if args.quiet {
+            self.verbosity = Verbosity::Quiet;
+        }
+
+        if let (Some(birthday), Some(birthyear)) = (&args.birthday, &args.birthyear) {
+            bail!(
+                "--birthday '{}' and --birthyear '{}' both give your birthday. Pass --birthday \
+                 for a full date, or --birthyear if you only know the year",
+                birthday,
+                birthyear
+            );
+        }
+        #[cfg(feature = "famous")]
+        if let (Some(name), Some(birthyear)) = (&args.famous, &args.birthyear) {
+            bail!(
+                "--famous '{}' and --birthyear '{}' both give your birthday. Pass only one",
+                name,
+                birthyear
+            );
+        }
+        #[cfg(feature = "famous")]
+        if let Some(name) = &args.famous {
+            self.set_birthday(DateSpecifier::full(famous::birthday(name)?), Layer::Args);
+        }
+        if let Some(birthday) = args.birthday.as_ref().or(args.since.as_ref()) {
+            self.set_birthday(DateSpecifier::full(birthday), Layer::Args);
+            self.since = args.birthday.is_none();
+        } else if let Some(birthyear) = &args.birthyear {
+            self.set_birthday(DateSpecifier::year(birthyear), Layer::Args);
+        } else if let Some(month_year) = &args.birthmonth_year {
+            self.set_birthday(DateSpecifier::month_year(month_year), Layer::Args);
+        }
+
+        if let Some(format) = &args.output_date_format {
+            let format: DateFormat = format
+                .parse()
+                .map_err(|err| eyre!("Invalid --output-date-format '{}': {}", format, err))?;
+            self.output_date_format = Some(format.as_fmt_string());
+        }
+
+        if let Some(years) = args.life_expectancy {
+            self.life_expectancy = Some(years.unwrap_or(self.default_life_expectancy));
+        }
+
+        if let Some(date) = args.at.first().or(args.date.as_ref()) {
+            self.set_current_date(DateSpecifier::full(date), Layer::Args);
+        } else if let Some(year) = &args.year {
+            self.set_current_date(DateSpecifier::year(year), Layer::Args);
+        }
+
+        if args.format.as_deref() == Some(DETECT_FORMAT) {
+            self.detect_format = true;
+        } else if let Some(format) = &args.format {
+            let format = format
+                .parse()
+                .map_err(|err| eyre!("Invalid --format '{}': {}", format, err))?;
+            self.set_format(format, Layer::Args);
+        } else if let Some(preset) = args.format_preset {
+            self.set_format(preset.format(), Layer::Args);
+        } else if let Some(locale) = &args.locale {
+            let format = DateFormat::for_locale(locale)
+                .ok_or_else(|| eyre!("Unknown --locale '{}'", locale))?;
+            self.set_format(format, Layer::Args);
+        }
+
+        if let Some(greeting) = &args.greeting {
+            self.greeting = Some(greeting.clone());
+        }
+        Ok(self)
+    }
+
+    /// For `--format detect`, the format inferred from `birthday`. `None` keeps the
+    /// format from the other layers, as when the birthday could be read more than one way
+    fn detected_format(&self, birthday: &DateSpecifier) -> Option<DateFormat> {
+        if !self.detect_format {
+            return None;
+        }
+        let sample = match birthday {
+            DateSpecifier::Full(date) => split_time(date).0,
+            _ => "",
+        };
+        let format = DateFormat::detect(sample);
+        if format.is_none() && self.verbosity >= Verbosity::Normal {
+            eprintln!(
+                "Could not detect the date format from '{}', using {}",
+                sample, self.format
+            );
+        }
+        format
+    }
+
+    /// Stack nothing with `--no-config`. Otherwise stack `~/.birthday` beneath the config
+    /// file named by `--config`, or beneath the first that exists of
+    /// `$XDG_CONFIG_HOME/howoldami/config.toml`, `config.toml` in the platform's config
+    /// directory, and `./howoldami.toml`
+    ///
+    /// A `--profile` that none of them define is an error
+    pub fn stack_config_layer(self, args: &Args) -> Result<Self> {
+        if args.no_config {
+            return Ok(self);
+        }
+        let builder = match self.birthday_file.clone() {
+            Some(path) => self.stack_birthday_file_layer(&path),
+            None => self,
+        };
+        let builder = match &args.config {
+            Some(path) => builder.stack_explicit_file_layer(path)?,
+            None => builder.stack_first_file_layer(&config_search_paths())?,
+        };
+        if let (Some(profile), false) = (&builder.profile, builder.profile_found) {
+            bail!("No profile named '{}' in the config file", profile);
+        }
+        Ok(builder)
+    }
+
+    /// Read a birthday from the first line of `path`, if it exists. Every other layer
+    /// overrides it, so stack it first
+    pub fn stack_birthday_file_layer(mut self, path: &Path) -> Self {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return self;
+        };
+        match contents.lines().next().map(str::trim) {
+            Some(line) if !line.is_empty() => {
+                self.set_birthday(DateSpecifier::full(line), Layer::BirthdayFile)
+            }
+            _ => {
+                if self.verbosity >= Verbosity::Verbose {
+                    eprintln!("{} has no birthday on its first line", path.display());
+                }
+            }
+        }
+        self
+    }
+
+    /// Stack the first of `paths` that exists, if any. With `--strict-config`, one that can't
+    /// be read or parsed is an error rather than a warning
+    pub fn stack_first_file_layer(self, paths: &[PathBuf]) -> Result<Self> {
+        match paths.iter().find(|path| path.is_file()) {
+            Some(path) if self.strict_config => self.stack_explicit_file_layer(path),
+            Some(path) => Ok(self.stack_file_layer(path)),
+            None => {
+                if self.verbosity >= Verbosity::Verbose {
+                    eprintln!("No config file found");
+                }
+                Ok(self)
+            }
+        }
+    }
+
+    /// Unlike [`Self::stack_file_layer`], a file that can't be read is an error
+    pub fn stack_explicit_file_layer(self, path: &Path) -> Result<Self> {
+        let config = ConfigFile::from_file(path)
+            .wrap_err_with(|| format!("Could not read config file {}", path.display()))?;
+        Ok(self.stack_config(config))
+    }
+
+    /// A missing file is skipped quietly, but one that can't be read or parsed is
+    /// skipped with a warning saying why
+    pub fn stack_file_layer(self, path: &Path) -> Self {
+        match ConfigFile::from_file(path) {
+            Ok(config) => self.stack_config(config),
+            Err(err) if is_not_found(&err) => {
+                if self.verbosity >= Verbosity::Verbose {
+                    eprintln!("No config file at {}", path.display());
+                }
+                self
+            }
+            Err(err) => {
+                if self.verbosity >= Verbosity::Normal {
+                    eprintln!("Ignoring config file {}: {:#}", path.display(), err);
+                }
+                self
+            }
+        }
+    }
+
+    fn stack_config(mut self, mut config: ConfigFile) -> Self {
+        // Redundant if both are set - birthday takes precedence
+        if config.birthday.is_some()
+            && config.birthyear.is_some()
+            && self.verbosity >= Verbosity::Verbose
+        {
+            eprintln!("The config file sets both birthday and birthyear, so birthyear is ignored");
+        }
+        if let Some(birthday) = config.birthday {
+            self.set_birthday(DateSpecifier::full(&birthday), Layer::File);
+        } else if let Some(birthyear) = config.birthyear {
+            self.set_birthday(DateSpecifier::year(&birthyear), Layer::File);
+        }
+
+        if let Some(current_date) = config.current_date {
+            self.set_current_date(DateSpecifier::full(&current_date), Layer::File);
+        } else if let Some(current_year) = config.current_year {
+            self.set_current_date(DateSpecifier::year(&current_year), Layer::File);
+        }
+
+        if let Some(format) = config.format {
+            self.set_format(format, Layer::File);
+        }
+
+        if let Some(verbosity) = config.verbosity {
+            self.verbosity = verbosity;
+        }
+
+        if self.verbosity >= Verbosity::Verbose {
+            for key in &config.unknown_keys {
+                eprintln!("Ignoring unknown config key '{}'", key);
+            }
+        }
+
+        if let Some(greeting) = config.greeting {
+            self.greeting = Some(greeting);
+        }
+
+        if let Some(years) = config.life_expectancy {
+            self.default_life_expectancy = years;
+        }
+
+        if let Some(profile) = self
+            .profile
+            .as_ref()
+            .and_then(|name| config.profiles.remove(name))
+        {
+            if let Some(birthday) = &profile.birthday {
+                self.set_birthday(DateSpecifier::full(birthday), Layer::File);
+            } else if let Some(birthyear) = &profile.birthyear {
+                self.set_birthday(DateSpecifier::year(birthyear), Layer::File);
+            }
+            if let Some(format) = profile.format {
+                self.set_format(format, Layer::File);
+            }
+            self.profile_found = true;
+        }
+
+        for person in config.person {
+            let birthday = if let Some(birthday) = person.birthday {
+                Some(DateSpecifier::full(&birthday))
+            } else {
+                person
+                    .birthyear
+                    .map(|birthyear| DateSpecifier::year(&birthyear))
+            };
+            self.people.push(Person {
+                name: person.name,
+                birthday,
+            });
+            self.provenance.birthday = Layer::File;
+        }
+        self
+    }
+
+    /// Sits between the config file and the command line in precedence
+    pub fn stack_env_layer(mut self) -> Result<Self> {
+        if let Some(birthday) = env_var(BIRTHDAY_ENV_VAR)? {
+            self.set_birthday(DateSpecifier::full(&birthday), Layer::Env);
+        }
+
+        if let Some(format) = env_var(FORMAT_ENV_VAR)? {
+            let format = format
+                .parse()
+                .map_err(|err| eyre!("Invalid {} '{}': {}", FORMAT_ENV_VAR, format, err))?;
+            self.set_format(format, Layer::Env);
+        }
+        Ok(self)
+    }
+
+    /// Read the birthday from the first line of `reader`, usually stdin
+    pub fn stack_reader_layer(mut self, mut reader: impl BufRead) -> Result<Self> {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .wrap_err("Could not read a birthday from stdin")?;
+        let line = line.trim();
+        if line.is_empty() {
+            bail!("No birthday found on stdin");
+        }
+        self.set_birthday(DateSpecifier::full(line), Layer::Stdin);
+        Ok(self)
+    }
+
+    /// Ask for a birthday on `prompt` and read it from `reader`, only if no other layer
+    /// gave one. An empty answer leaves the birthday missing
+    pub fn stack_prompt_layer(
+        mut self,
+        mut reader: impl BufRead,
+        prompt: &mut impl io::Write,
+    ) -> Result<Self> {
+        if self.birthday.is_some() || self.has_people() {
+            return Ok(self);
+        }
+        write!(prompt, "When is your birthday? ")?;
+        prompt.flush()?;
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .wrap_err("Could not read a birthday from the prompt")?;
+        let line = line.trim();
+        if !line.is_empty() {
+            self.set_birthday(DateSpecifier::full(line), Layer::Prompt);
+        }
+        Ok(self)
+    }
+
+    /// Explain a later layer's full birthday replacing an earlier birth year, or the
+    /// other way around, since which one wins isn't obvious
+    fn birthday_override_note(&self, birthday: &DateSpecifier, layer: Layer) -> Option<String> {
+        let previous = self.birthday.as_ref()?;
+        if std::mem::discriminant(previous) == std::mem::discriminant(birthday) {
+            return None;
+        }
+        let kind = |specifier: &DateSpecifier| match specifier {
+            DateSpecifier::Full(_) => "birthday",
+            DateSpecifier::YearOnly(_) => "birthyear",
+            DateSpecifier::MonthYear(_) => "birth month",
+        };
+        Some(format!(
+            "Using {} '{}' from {} over {} '{}' from {}",
+            kind(birthday),
+            birthday,
+            layer,
+            kind(previous),
+            previous,
+            self.provenance.birthday
+        ))
+    }
+
+    /// A birthday from any layer replaces a household from an earlier one
+    fn set_birthday(&mut self, birthday: DateSpecifier, layer: Layer) {
+        if self.verbosity >= Verbosity::Verbose {
+            if let Some(note) = self.birthday_override_note(&birthday, layer) {
+                eprintln!("{}", note);
+            }
+        }
+        self.birthday = Some(birthday);
+        self.people.clear();
+        self.provenance.birthday = layer;
+    }
+
+    fn set_current_date(&mut self, current_date: DateSpecifier, layer: Layer) {
+        self.current_date = Some(current_date);
+        self.provenance.current_date = layer;
+    }
+
+    fn set_format(&mut self, format: DateFormat, layer: Layer) {
+        self.format = format;
+        self.provenance.format = layer;
+    }
+
+    /// Describe where each resolved setting came from
+    pub fn explain(&self) -> Vec<String> {
+        let birthday = match &self.birthday {
+            Some(birthday) => format!("birthday: {} (from {})", birthday, self.provenance.birthday),
+            None if self.has_people() => format!(
+                "birthday: {} people (from {})",
+                self.people.len(),
+                self.provenance.birthday
+            ),
+            None => "birthday: not set".to_owned(),
+        };
+        let detected = self
+            .birthday
+            .as_ref()
+            .and_then(|birthday| self.detected_format(birthday));
+        let format = match &detected {
+            Some(format) => format!("format: {} (from {})", format, Layer::Args),
+            None => format!("format: {} (from {})", self.format, self.provenance.format),
+        };
+        let current_date = match &self.current_date {
+            Some(current_date) => format!(
+                "current_date: {} (from {})",
+                current_date, self.provenance.current_date
+            ),
+            None => "current_date: today (from the system clock)".to_owned(),
+        };
+        vec![birthday, format, current_date]
+    }
+
+    /// Whether a household was configured. A birthday given on the command line
+    /// replaces the household
+    pub fn has_people(&self) -> bool {
+        !self.people.is_empty()
+    }
+
+    pub fn build(&self) -> Result<App> {
+        let Some(birthday) = &self.birthday else {
+            return Err(ConfigError::MissingBirthday.into());
+        };
+        self.build_for(birthday, None)
+    }
+
+    /// Build an app as of each of `dates`, in order, replacing any current date
+    pub fn build_timeline(mut self, dates: &[String]) -> Result<Vec<App>> {
+        dates
+            .iter()
+            .map(|date| {
+                self.set_current_date(DateSpecifier::full(date), Layer::Args);
+                self.build()
+            })
+            .collect()
+    }
+
+    /// Build an app for each configured person, in config file order
+    pub fn build_people(&self) -> Result<Vec<(String, App)>> {
+        self.people
+            .iter()
+            .map(|person| {
+                let Some(birthday) = &person.birthday else {
+                    bail!("No birthday specified for {}", person.name);
+                };
+                let app = self.build_for(birthday, Some(&person.name))?;
+                Ok((person.name.clone(), app))
+            })
+            .collect()
+    }
+
+    /// Build an app for each non-blank line of `reader`, treating every line as a
+    /// birthday. A line that doesn't parse doesn't stop the rest from being built
+    pub fn build_batch(&self, reader: impl BufRead) -> Result<Vec<BatchLine>> {
+        let mut lines = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.wrap_err("Could not read batch file")?;
+            let input = line.trim();
+            if input.is_empty() {
+                continue;
+            }
+            lines.push(BatchLine {
+                line_number: index + 1,
+                input: input.to_owned(),
+                app: self.build_for(&DateSpecifier::full(input), None),
+            });
+        }
+        Ok(lines)
+    }
+
+    /// The wall clock time in whichever timezone today's date is based on
+    fn now(&self) -> NaiveDateTime {
+        let now = self.clock.now();
+        if let Some(timezone) = &self.timezone {
+            wall_clock(now, timezone)
+        } else if self.utc {
+            wall_clock(now, &Utc)
+        } else {
+            wall_clock(now, &Local)
+        }
+    }
+
+    fn build_for(&self, birthday: &DateSpecifier, name: Option<&str>) -> Result<App> {
+        // Dates stay unparsed until every layer has stacked, so a format from any layer
+        // applies to dates given in every other
+        let detected = self.detected_format(birthday);
+        let options = ParseOptions {
+            format_str: detected.as_ref().unwrap_or(&self.format).as_fmt_string(),
+            century_pivot: self.century_pivot,
+            strict: self.strict,
+            clamp_years: self.clamp_years,
+        };
+
+        let full_birthday = birthday.is_full();
+        let mut wish_happy_birthday = full_birthday;
+        let birth_time = birthday.time().unwrap_or_default();
+        let birthday = birthday.to_naive_date_with(&options)?;
+        let birthday = self
+            .calendar
+            .to_gregorian(birthday)
+            .ok_or_else(|| eyre!("Could not convert {} to the Gregorian calendar", birthday))?;
+
+        let relative = match &self.current_date {
+            Some(DateSpecifier::Full(expr)) => parse_relative_date(expr, self.now().date()),
+            _ => None,
+        };
+        let (current_date, current_time) = if let Some(current_date) = relative {
+            (current_date?, NaiveTime::default())
+        } else if let Some(current_date) = &self.current_date {
+            (
+                current_date.to_naive_date_with(&options)?,
+                current_date.time().unwrap_or_default(),
+            )
+        } else {
+            wish_happy_birthday = false;
+            let now = self.now();
+            (now.date(), now.time())
+        };
+
+        let (birthday, current_date) = if self.historical_year_math {
+            let converted = (
+                historical_to_astronomical(birthday)?,
+                historical_to_astronomical(current_date)?,
+            );
+            if self.verbosity >= Verbosity::Verbose {
+                for (historical, astronomical) in
+                    [(birthday, converted.0), (current_date, converted.1)]
+                {
+                    if historical != astronomical {
+                        eprintln!(
+                            "Reading year {} as {} BCE, astronomical year {}",
+                            historical.year(),
+                            -historical.year(),
+                            astronomical.year()
+                        );
+                    }
+                }
+            }
+            converted
+        } else {
+            (birthday, current_date)
+        };
+
+        let other_birthday = match &self.other_birthday {
+            Some(date) => Some(
+                DateSpecifier::full(date)
+                    .to_naive_date_with(&options)
+                    .wrap_err("Could not parse --other-birthday")?,
+            ),
+            None => None,
+        };
+
+        let birthdays_between = match self.birthdays_between.as_slice() {
+            [start, end] => {
+                let parse = |date: &str| {
+                    DateSpecifier::full(date)
+                        .to_naive_date_with(&options)
+                        .wrap_err("Could not parse --birthdays-between")
+                };
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    bail!(
+                        "The --birthdays-between start {} is after its end {}",
+                        start,
+                        end
+                    );
+                }
+                Some((start, end))
+            }
+            _ => None,
+        };
+
+        if birthday > current_date && self.since {
+            bail!("The --since date is after the current date");
+        }
+        if birthday > current_date && !self.allow_future {
+            bail!(
+                "birthday {} is after current date {}. Pass --allow-future to compute a negative age",
+                birthday,
+                current_date
+            );
+        }
+
+        let verbosity = self.verbosity;
+
+        Ok(App {
+            birthday,
+            birth_time,
+            current_date,
+            current_time,
+            verbosity,
+            wish_happy_birthday,
+            full_birthday,
+            countdown: self.countdown,
+            weekday: self.weekday,
+            unit: self.unit,
+            zodiac: self.zodiac,
+            greeting: self.greeting.clone(),
+            name: name.map(str::to_owned),
+            sleeps: self.sleeps,
+            half_birthday: self.half_birthday,
+            born_on_leap_year: self.born_on_leap_year,
+            milestones: self.milestones.clone(),
+            year_progress: self.year_progress,
+            color: self.color,
+            next_milestone: self.next_milestone,
+            dog_years: self.dog_years,
+            count_birthdays: self.count_birthdays,
+            timezone: self.timezone,
+            birthday_window: self.birthday_window,
+            other_birthday,
+            life_expectancy: self.life_expectancy,
+            retire_at: self.retire_at,
+            week_start: self.week_start,
+            heartbeats: self.heartbeats,
+            output_date_format: self.output_date_format.clone(),
+            birthdays_between,
+        })
+    }
+}
+
+// Flags go before or after the subcommand, and running with none is the same as `age`
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print your age (the default)
+    Age,
+    /// Print how long until your next birthday
+    Countdown,
+    /// Print your zodiac sign
+    Zodiac {
+        #[clap(value_enum, default_value_t = Zodiac::Western)]
+        system: Zodiac,
+    },
+    /// Print a completion script for a shell
+    #[clap(hide = true)]
+    Completions { shell: clap_complete::Shell },
+}
+
+/// Write a completion script covering every flag in `Args` for `shell`
+pub fn write_completions(shell: clap_complete::Shell, out: &mut impl io::Write) {
+    clap_complete::generate(shell, &mut Args::command(), THIS_PROGRAM_NAME, out);
+}
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
+    /// Increase message verbosity. Repeat for more detail, like -vv
+    #[clap(short, long, global = true, group = "verbosity", action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Silence all output except the user's age
+    #[clap(short, long, global = true, group = "verbosity")]
+    pub quiet: bool,
+
+    /// Override today's date, or offset it with something like "+30d" or "-1y".
+    /// Units are d, w, m, and y. Unix epoch seconds like "@1672531200" work here and in
+    /// --birthday
+    #[clap(
+        short,
+        long,
+        global = true,
+        group = "current_date",
+        allow_hyphen_values = true
+    )]
+    pub date: Option<String>,
+
+    /// Override today's date, but just the year
+    #[clap(short, long, global = true, group = "current_date")]
+    pub year: Option<String>,
+
+    /// Compute your age as of this date instead of today. Parsed with the
+    /// configured date format, and takes precedence over the real clock. Repeat to
+    /// print your age at each date, one per line
+    #[clap(
+        long,
+        global = true,
+        group = "current_date",
+        allow_hyphen_values = true
+    )]
+    pub at: Vec<String>,
+
+    /// Specify your birthday, optionally followed by a time of birth like "13:45"
+    #[clap(short, long, global = true, group = "birthday_specifier")]
+    pub birthday: Option<String>,
+
+    /// Specify just your birth year. Years before 1 CE use astronomical numbering,
+    /// where 0 is 1 BCE and -44 is 45 BCE, unless --historical-year-math is given
+    // Kept out of the birthday_specifier group so that combining it with --birthday
+    // gets a clearer error from stack_args_layer than clap's generic one
+    #[clap(long, global = true, allow_negative_numbers = true, conflicts_with_all = ["since", "stdin", "batch", "birthmonth_year"])]
+    pub birthyear: Option<String>,
+
+    /// Specify just the year and month you were born, like "1998-01"
+    #[clap(long, global = true, group = "birthday_specifier")]
+    pub birthmonth_year: Option<String>,
+
+    /// Print the years and days since this date, rather than your age
+    #[clap(long, global = true, group = "birthday_specifier")]
+    pub since: Option<String>,
+
+    /// Use the birthday of a well-known person, like "Ada Lovelace"
+    #[cfg(feature = "famous")]
+    #[clap(long, global = true, value_name = "NAME", group = "birthday_specifier")]
+    pub famous: Option<String>,
+
+    /// Two digit years at or above this are read as 19xx, and below it as 20xx
+    #[clap(long, global = true, default_value_t = DEFAULT_CENTURY_PIVOT, value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub century_pivot: u32,
+
+    /// Read your birthday from a line on standard input
+    #[clap(long, global = true, group = "birthday_specifier")]
+    pub stdin: bool,
+
+    /// Print the age for each birthday in this file, one per line
+    #[clap(long, global = true, group = "birthday_specifier")]
+    pub batch: Option<PathBuf>,
+
+    /// Datetime format, or "detect" to infer it from the birthday
+    #[clap(short, long, global = true)]
+    pub format: Option<String>,
+
+    /// Use a named datetime format instead of spelling one out with --format
+    #[clap(long, global = true, value_enum, conflicts_with = "format")]
+    pub format_preset: Option<FormatPreset>,
+
+    /// Pick the datetime format commonly used in a locale, like "en-GB". An explicit
+    /// --format or --format-preset wins
+    #[clap(long, global = true)]
+    pub locale: Option<String>,
+
+    /// Read this config file instead of the default one
+    #[clap(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Don't read any config file, even if one exists
+    #[clap(long, global = true, conflicts_with = "config")]
+    pub no_config: bool,
+
+    /// Replace the birthday greeting. "{age}" and "{name}" are filled in
+    #[clap(long, global = true)]
+    pub greeting: Option<String>,
+
+    /// Print an annotated example config file and exit
+    #[clap(long, global = true)]
+    pub config_schema: bool,
+
+    /// Print example --format values and exit
+    #[clap(long, global = true)]
+    pub list_formats: bool,
+
+    /// For cron: print the greeting if today is your birthday, and otherwise print
+    /// nothing and exit with code 3
+    #[clap(long, global = true, conflicts_with_all = ["batch", "porcelain", "compact"])]
+    pub notify_on_birthday: bool,
+
+    /// Ask for your birthday if nothing else gives one. Only when stdin is a terminal
+    #[clap(long, global = true, conflicts_with_all = ["stdin", "batch"])]
+    pub interactive: bool,
+
+    /// Print which config layer provided the birthday, format, and current date, then exit
+    #[clap(long, global = true)]
+    pub explain: bool,
+
+    /// Print age as years, months, and days
+    #[clap(long, global = true, conflicts_with = "decimal")]
+    pub precise: bool,
+
+    /// Print age as a fractional number of years
+    #[clap(long, global = true)]
+    pub decimal: bool,
+
+    /// Allow a birthday after the current date, reporting a negative age
+    #[clap(long, global = true)]
+    pub allow_future: bool,
+
+    /// Number of decimal places to print with --decimal, at most 15
+    #[clap(long, global = true, default_value_t = 2, value_parser = clap::value_parser!(u8).range(..=15))]
+    pub precision: u8,
+
+    /// How --decimal reduces your age to --precision places
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub rounding: Rounding,
+
+    /// Print this instead of the bare age. Fills in {age}, {years}, {days},
+    /// {birthday}, and {today}
+    #[clap(long, global = true, conflicts_with_all = ["precise", "decimal"])]
+    pub template: Option<String>,
+
+    /// Output format for the result
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub format_output: OutputFormat,
+
+    /// Also print the number of days until your next birthday
+    #[clap(long, global = true)]
+    pub countdown: bool,
+
+    /// Also print the day of the week you were born on
+    #[clap(long, global = true)]
+    pub weekday: bool,
+
+    /// Unit to report the age in
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub unit: Unit,
+
+    /// Also print your zodiac sign
+    #[clap(long, global = true, value_enum)]
+    pub zodiac: Option<Zodiac>,
+
+    /// Also print how many nights are left until your next birthday
+    #[clap(long, global = true)]
+    pub sleeps: bool,
+
+    /// Base today's date on UTC instead of the local timezone
+    #[clap(long, global = true)]
+    pub utc: bool,
+
+    /// Base today's date on this IANA timezone, like "Asia/Tokyo"
+    #[clap(long, global = true, conflicts_with = "utc")]
+    pub timezone: Option<chrono_tz::Tz>,
+
+    /// Also print your next half-birthday and how far away it is
+    #[clap(long, global = true)]
+    pub half_birthday: bool,
+
+    /// Mention when you were born in a leap year
+    #[clap(long, global = true)]
+    pub born_on_leap_year: bool,
+
+    /// List the dates of your upcoming round birthdays (30, 40, 50...)
+    #[clap(long, global = true)]
+    pub milestones: bool,
+
+    /// List the dates of these upcoming birthdays instead, like 18,21,65
+    #[clap(long, global = true, value_delimiter = ',')]
+    pub milestone_ages: Vec<u32>,
+
+    /// Print how far you are from your last birthday to your next
+    #[clap(long, global = true)]
+    pub year_progress: bool,
+
+    /// Print nothing and exit successfully only if your age is at least this
+    #[clap(long, global = true)]
+    pub at_least: Option<i64>,
+
+    /// Print nothing and exit successfully only if your age is at most this
+    #[clap(long, global = true)]
+    pub at_most: Option<i64>,
+
+    /// Color the age and birthday greeting. Never applies to JSON output
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub color: ColorChoice,
+
+    /// Also print your next round birthday, when it is, and how far away it is
+    #[clap(long, global = true)]
+    pub next_milestone: bool,
+
+    /// Round birthdays for --next-milestone are multiples of this
+    #[clap(long, global = true, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    pub milestone_step: u32,
+
+    /// Print the birthday and current date in ISO 8601 to stderr, as they were parsed
+    #[clap(long, global = true)]
+    pub show_parsed: bool,
+
+    /// Also print your age in dog years
+    #[clap(long, global = true)]
+    pub dog_years: bool,
+
+    /// How --dog-years converts your age
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub dog_year_formula: DogYearFormula,
+
+    /// Print only "age<TAB>birthday<TAB>today" on one line, whatever else is passed.
+    /// This output is stable for scripts
+    #[clap(long, global = true, conflicts_with_all = ["template", "precise", "decimal", "format_output", "since", "other_birthday", "birthdays_between"])]
+    pub porcelain: bool,
+
+    /// Print only the age in --unit, without even a trailing newline, for shell prompts
+    #[clap(long, global = true, conflicts_with_all = ["porcelain", "template", "precise", "decimal", "format_output", "since", "other_birthday", "birthdays_between"])]
+    pub compact: bool,
+
+    /// Keep zero years, months, or days in --format-output iso-duration, like P26Y0M14D
+    #[clap(long, global = true)]
+    pub duration_zeros: bool,
+
+    /// Write the output to this file instead of stdout, replacing anything in it
+    #[clap(long, global = true)]
+    pub output: Option<PathBuf>,
+
+    /// Also print how many birthdays you've had, counting today's
+    #[clap(long, global = true)]
+    pub count_birthdays: bool,
+
+    /// How to print errors
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub error_format: ErrorFormat,
+
+    /// Print the number of days you've been alive instead of your age
+    #[clap(long, global = true, conflicts_with_all = ["template", "precise", "decimal"])]
+    pub days_alive: bool,
+
+    /// Print which week of your life you're in, starting at week 1 on your birthday
+    #[clap(long, global = true, conflicts_with_all = ["template", "precise", "decimal", "days_alive"])]
+    pub week_number: bool,
+
+    /// How to write dates in messages, like --format. ISO 8601 if not given
+    #[clap(long, global = true)]
+    pub output_date_format: Option<String>,
+
+    /// Estimate how many times your heart has beaten, counting from your birth time if
+    /// given
+    #[clap(long, global = true)]
+    pub heartbeats: bool,
+
+    /// Average heart rate for --heartbeats, in beats per minute
+    #[clap(long, global = true, default_value_t = 72, value_parser = clap::value_parser!(u32).range(1..))]
+    pub bpm: u32,
+
+    /// Print the age you turn on your birthday this year, even if it hasn't come yet
+    #[clap(long, global = true, conflicts_with_all = ["template", "precise", "decimal", "days_alive", "week_number"])]
+    pub turning: bool,
+
+    /// Print the age you turned on your most recent birthday, and its date
+    #[clap(long, global = true, conflicts_with_all = ["template", "precise", "decimal", "days_alive", "week_number", "turning"])]
+    pub as_of_last_birthday: bool,
+
+    /// Print which day of this year, from 1 to 366, your birthday falls on
+    #[clap(long, global = true, conflicts_with_all = ["template", "precise", "decimal", "days_alive", "week_number", "turning", "as_of_last_birthday"])]
+    pub day_of_year: bool,
+
+    /// Also mention your birthday when it is within this many days of today
+    #[clap(long, global = true, value_name = "DAYS", default_value_t = 0)]
+    pub birthday_window: u32,
+
+    /// Which calendar the birthday is written in
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub calendar: Calendar,
+
+    /// Compare your age with someone born on this date
+    #[clap(long, global = true, conflicts_with_all = ["since", "batch"])]
+    pub other_birthday: Option<String>,
+
+    /// List your birthdays from START to END, and the age you turn on each
+    #[clap(
+        long,
+        global = true,
+        num_args = 2,
+        value_names = ["START", "END"],
+        conflicts_with_all = ["since", "batch", "other_birthday"]
+    )]
+    pub birthdays_between: Vec<String>,
+
+    /// Use the birthday and format from this `[profiles.<NAME>]` table of the config file
+    #[clap(long, global = true, value_name = "NAME", conflicts_with = "no_config")]
+    pub profile: Option<String>,
+
+    /// Show how much of a life expectancy you've lived. Without YEARS, uses the config file's
+    /// `life_expectancy`, or 73
+    #[clap(long, global = true, value_name = "YEARS", num_args = 0..=1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub life_expectancy: Option<Option<u32>>,
+
+    /// Show when you can retire at AGE, 65 if not given
+    #[clap(long, global = true, value_name = "AGE", num_args = 0..=1, default_missing_value = "65")]
+    pub retire_at: Option<u32>,
+
+    /// Count weeks of life in calendar weeks starting on this day, rather than from
+    /// the weekday you were born on
+    #[clap(long, global = true, value_enum)]
+    pub week_start: Option<WeekStart>,
+
+    /// Read years before 1 CE historically, where -1 is 1 BCE and there is no year 0.
+    /// Ages spanning 1 BCE to 1 CE come out a year less than with astronomical years
+    #[clap(long, global = true)]
+    pub historical_year_math: bool,
+
+    /// Reject dates like 01/02/2020 whose day and month could be swapped, rather than
+    /// reading them with --format
+    #[clap(long, global = true)]
+    pub strict: bool,
+
+    /// Make a config file that can't be read or parsed an error, rather than a warning
+    #[clap(long, global = true)]
+    pub strict_config: bool,
+
+    /// Clamp years outside the supported range to its nearest end, rather than erroring
+    #[clap(long, global = true)]
+    pub clamp_years: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+
+    #[test]
+    fn on_actual_birthday() {
+        let birthyear = 1998;
+        let diff = 0;
+        let currentyear = birthyear + diff;
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let age = app.calculate().age;
+        assert_eq!(age, i64::from(diff));
+    }
+
+    #[test]
+    fn on_birthday() {
+        let birthyear = 1998;
+        let diff = 26;
+        let currentyear = birthyear + diff;
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let age = app.calculate().age;
+        assert_eq!(age, i64::from(diff));
+    }
+
+    #[test]
+    fn on_birthday_really_young() {
+        let birthyear = 1998;
+        let diff = 1;
+        let currentyear = birthyear + diff;
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let age = app.calculate().age;
+        assert_eq!(age, i64::from(diff));
+    }
+
+    #[test]
+    fn on_birthday_really_old() {
+        let birthyear = 1998;
+        let diff = 1000;
+        let currentyear = birthyear + diff;
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let age = app.calculate().age;
+        assert_eq!(age, i64::from(diff));
+    }
+
+    #[test]
+    fn day_before_birthday() {
+        let birthyear = 1998;
+        let diff = 26;
+        let currentyear = birthyear + diff;
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 2).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let age = app.calculate().age;
+        assert_eq!(age, i64::from(diff - 1));
+    }
+
+    #[test]
+    fn day_after_birthday() {
+        let birthyear = 1998;
+        let diff = 26;
+        let currentyear = birthyear + diff;
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 2).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let age = app.calculate().age;
+        assert_eq!(age, i64::from(diff));
+    }
+
+    #[test]
+    fn precise_before_day_of_month() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 3, 15).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let expected = Breakdown {
+            years: 26,
+            months: 2,
+            days: 26,
+        };
+        assert_eq!(app.breakdown(), Some(expected));
+    }
+
+    #[test]
+    fn precise_after_day_of_month() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 3, 15).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 20).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let expected = Breakdown {
+            years: 26,
+            months: 3,
+            days: 5,
+        };
+        assert_eq!(app.breakdown(), Some(expected));
+    }
+
+    #[test]
+    fn precise_on_day_of_month() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 3, 15).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let expected = Breakdown {
+            years: 26,
+            months: 3,
+            days: 0,
+        };
+        assert_eq!(app.breakdown(), Some(expected));
+    }
+
+    #[test]
+    fn precise_across_short_month() {
+        let from = NaiveDate::from_ymd_opt(2023, 1, 31).unwrap();
+        let to = NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+        let expected = Breakdown {
+            years: 0,
+            months: 1,
+            days: 1,
+        };
+        assert_eq!(Breakdown::between(from, to), Some(expected));
+    }
+
+    #[test]
+    fn precise_display() {
+        let breakdown = Breakdown {
+            years: 26,
+            months: 3,
+            days: 14,
+        };
+        assert_eq!(breakdown.to_string(), "26 years, 3 months, 14 days");
+    }
+
+    #[test]
+    fn json_report() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            wish_happy_birthday: true,
+            verbosity: Verbosity::Verbose,
+            ..Default::default()
+        };
+        let report = app.calculate();
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"age":26,"birthday":"1998-01-01","current_date":"2024-01-01"}"#
+        );
+        assert!(report.messages.contains(&"Happy birthday!".to_owned()));
+    }
+
+    #[test]
+    fn quiet_report_has_no_messages() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            wish_happy_birthday: true,
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let report = app.calculate();
+        assert!(report.messages.is_empty());
+    }
+
+    #[test]
+    fn birthday_in_future() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            wish_happy_birthday: false,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let report = app.calculate();
+        assert_eq!(report.age, -6);
+        assert_eq!(report.messages, vec!["Born 6 years in the future"]);
+        assert_eq!(app.breakdown(), None);
+    }
+
+    #[test]
+    fn no_greeting_before_birth() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            wish_happy_birthday: true,
+            full_birthday: true,
+            countdown: true,
+            verbosity: Verbosity::Normal,
+            ..Default::default()
+        };
+        let report = app.calculate();
+        assert_eq!(report.messages, vec!["Born 6 years in the future"]);
+        assert_eq!(report.extras, vec!["2192 days until you are born"]);
+        assert_eq!(app.birthday_greeting(), None);
+    }
+
+    #[test]
+    fn next_birthday_later_this_year() {
+        let birthday = NaiveDate::from_ymd_opt(1998, 6, 15).unwrap();
+        let on = NaiveDate::from_ymd_opt(2024, 1, 24).unwrap();
+        let next = next_birthday(birthday, on).unwrap();
+        assert_eq!(next, NaiveDate::from_ymd_opt(2024, 6, 15).unwrap());
+        assert_eq!((next - on).num_days(), 143);
+    }
+
+    #[test]
+    fn next_birthday_wraps_to_next_year() {
+        let birthday = NaiveDate::from_ymd_opt(1998, 1, 1).unwrap();
+        let on = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let next = next_birthday(birthday, on).unwrap();
+        assert_eq!(next, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn next_birthday_is_today() {
+        let birthday = NaiveDate::from_ymd_opt(1998, 3, 4).unwrap();
+        let on = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        assert_eq!(next_birthday(birthday, on), Some(on));
+    }
+
+    #[test]
+    fn next_birthday_leap_day() {
+        let birthday = NaiveDate::from_ymd_opt(1996, 2, 29).unwrap();
+        let on = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        let next = next_birthday(birthday, on).unwrap();
+        assert_eq!(next, NaiveDate::from_ymd_opt(2023, 2, 28).unwrap());
+    }
+
+    #[test]
+    fn countdown_messages() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 2).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            countdown: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            app.calculate().extras,
+            vec!["1 day until your next birthday"]
+        );
+
+        app.current_date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        assert_eq!(app.calculate().extras, vec!["Today is your birthday!"]);
+    }
+
+    #[test]
+    fn born_on_a_thursday() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            weekday: true,
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().extras, vec!["You were born on a Thursday."]);
+
+        app.verbosity = Verbosity::Quiet;
+        assert!(app.calculate().extras.is_empty());
+    }
+
+    #[test]
+    fn iso_date_with_mdy_format() {
+        let format = DateFormat::MDY {
+            separator: "/".into(),
+        }
+        .as_fmt_string();
+        let date = DateSpecifier::full("1998-01-31")
+            .to_naive_date(&format)
+            .unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1998, 1, 31).unwrap());
+    }
+
+    #[test]
+    fn configured_format_still_parses() {
+        let format = DateFormat::MDY {
+            separator: "/".into(),
+        }
+        .as_fmt_string();
+        let date = DateSpecifier::full("01/31/1998")
+            .to_naive_date(&format)
+            .unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1998, 1, 31).unwrap());
+    }
+
+    #[test]
+    fn month_names() {
+        let format = DateFormat::default().as_fmt_string();
+        let expected = NaiveDate::from_ymd_opt(1998, 1, 1).unwrap();
+        for input in [
+            "January 1 1998",
+            "january 1 1998",
+            "Jan 1 1998",
+            "JAN 1, 1998",
+            "1 Jan 1998",
+            "1 January 1998",
+        ] {
+            let date = DateSpecifier::full(input).to_naive_date(&format).unwrap();
+            assert_eq!(date, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn strict_rejects_ambiguous_dates() {
+        let options = ParseOptions {
+            strict: true,
+            ..ParseOptions::new(&DateFormat::default().as_fmt_string())
+        };
+        let parse = |input| DateSpecifier::full(input).to_naive_date_with(&options);
+        let err = parse("01/02/2020").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'01/02/2020' is ambiguous, since its day and month could be swapped. Write it as \
+             2020-01-02 to confirm"
+        );
+        assert_eq!(
+            parse("01/13/2020").unwrap(),
+            NaiveDate::from_ymd_opt(2020, 1, 13).unwrap()
+        );
+        assert_eq!(
+            parse("03/03/2020").unwrap(),
+            NaiveDate::from_ymd_opt(2020, 3, 3).unwrap()
+        );
+        assert!(parse("2020-01-02").is_ok());
+        assert!(parse("Jan 2 2020").is_ok());
+
+        let lenient = ParseOptions::new(&DateFormat::default().as_fmt_string());
+        assert!(DateSpecifier::full("01/02/2020")
+            .to_naive_date_with(&lenient)
+            .is_ok());
+    }
+
+    #[test]
+    fn epoch_dates() {
+        let format = DateFormat::default().as_fmt_string();
+        let parse = |input| DateSpecifier::full(input).to_naive_date(&format);
+        assert_eq!(
+            parse("@1672531200").unwrap(),
+            NaiveDate::from_ymd_opt(2023, 1, 1).unwrap()
+        );
+        assert_eq!(
+            parse("@-86400").unwrap(),
+            NaiveDate::from_ymd_opt(1969, 12, 31).unwrap()
+        );
+        assert_eq!(
+            DateSpecifier::full("@1672574400").time(),
+            NaiveTime::from_hms_opt(12, 0, 0)
+        );
+        assert!(parse("@soon").is_err());
+    }
+
+    #[test]
+    fn epoch_birthday_and_date() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "@-86400",
+            "--date",
+            "@1672531200",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(app.calculate().age, 53);
+    }
+
+    #[test]
+    fn format_examples_cover_every_order() {
+        let examples = format_examples();
+        for order in ["MDY", "DMY", "YMD"] {
+            assert!(
+                examples.iter().any(|line| line.starts_with(order)),
+                "{}",
+                order
+            );
+        }
+        assert!(examples.contains(&"MDY/   12/31/1998".to_owned()));
+        assert!(examples.contains(&"DMY.   31.12.1998".to_owned()));
+        assert!(examples.contains(&"YMD-   1998-12-31".to_owned()));
+    }
+
+    #[test]
+    fn ordinal_suffixes() {
+        let format = DateFormat::default().as_fmt_string();
+        let parse = |input| DateSpecifier::full(input).to_naive_date(&format).unwrap();
+        let new_year = NaiveDate::from_ymd_opt(1998, 1, 1).unwrap();
+        assert_eq!(parse("1st January 1998"), new_year);
+        assert_eq!(parse("January 1st, 1998"), new_year);
+        assert_eq!(parse("Jan 1ST 1998"), new_year);
+        assert_eq!(
+            parse("3rd Mar 2000"),
+            NaiveDate::from_ymd_opt(2000, 3, 3).unwrap()
+        );
+        assert_eq!(
+            parse("3rd Mar, 2000"),
+            NaiveDate::from_ymd_opt(2000, 3, 3).unwrap()
+        );
+        assert_eq!(
+            parse("22nd Feb 2000"),
+            NaiveDate::from_ymd_opt(2000, 2, 22).unwrap()
+        );
+        assert_eq!(
+            parse("11th Nov 2011"),
+            NaiveDate::from_ymd_opt(2011, 11, 11).unwrap()
+        );
+        // The wrong suffix for the number is forgiven
+        assert_eq!(
+            parse("2st Feb 2000"),
+            NaiveDate::from_ymd_opt(2000, 2, 2).unwrap()
+        );
+        assert!(DateSpecifier::full("1xx Jan 1998")
+            .to_naive_date(&format)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_month_name() {
+        let format = DateFormat::default().as_fmt_string();
+        assert!(DateSpecifier::full("Janbruary 1 1998")
+            .to_naive_date(&format)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_birthyear_errors() {
+        let mut builder = LayeredAppConfigBuilder::new();
+        builder.birthday = Some(DateSpecifier::year("999999"));
+        builder.current_date = Some(DateSpecifier::year("2024"));
+        let err = builder.build().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Year 999999 is out of the supported range, -262143 to 262142. Pass --clamp-years \
+             to use the nearest supported year"
+        );
+    }
+
+    #[test]
+    fn overflowing_years() {
+        let options = ParseOptions::new("%Y-%m-%d");
+        for year in ["2147483648", "99999999999999999999999", "-2147483649"] {
+            let err = DateSpecifier::year(year)
+                .to_naive_date_with(&options)
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("out of the supported range"),
+                "{}",
+                err
+            );
+        }
+
+        let clamping = ParseOptions {
+            clamp_years: true,
+            ..options
+        };
+        let year = |year| {
+            DateSpecifier::year(year)
+                .to_naive_date_with(&clamping)
+                .unwrap()
+                .year()
+        };
+        assert_eq!(year("2147483648"), NaiveDate::MAX.year());
+        assert_eq!(year("-99999999999999999999999"), NaiveDate::MIN.year());
+        assert_eq!(year("1998"), 1998);
+        assert!(DateSpecifier::year("nineteen")
+            .to_naive_date_with(&clamping)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_format_arg_errors() {
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--format", "ABC-"]);
+        let err = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid --format 'ABC-': Invalid date format"
+        );
+    }
+
+    #[test]
+    fn age_in_each_unit() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 3, 15).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            ..Default::default()
+        };
+        for (unit, expected) in [
+            (Unit::Days, 9584),
+            (Unit::Weeks, 1369),
+            (Unit::Months, 314),
+            (Unit::Years, 26),
+        ] {
+            app.unit = unit;
+            assert_eq!(app.calculate().age, expected, "{:?}", unit);
+        }
+    }
+
+    #[test]
+    fn json_report_includes_non_default_unit() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(1998, 1, 11).unwrap(),
+            unit: Unit::Days,
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&app.calculate()).unwrap(),
+            r#"{"age":10,"unit":"days","birthday":"1998-01-01","current_date":"1998-01-11"}"#
+        );
+    }
+
+    #[test]
+    fn household_config() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"
+[[person]]
+name = "Alice"
+birthday = "01/01/1998"
+
+[[person]]
+name = "Bob"
+birthyear = "2000"
+"#
+        )
+        .unwrap();
+
+        let mut builder = LayeredAppConfigBuilder::new().stack_file_layer(file.path());
+        builder.current_date = Some(DateSpecifier::full("06/01/2024"));
+        assert!(builder.has_people());
+
+        let ages: Vec<_> = builder
+            .build_people()
+            .unwrap()
+            .into_iter()
+            .map(|(name, app)| (name, app.calculate().age))
+            .collect();
+        assert_eq!(ages, vec![("Alice".to_owned(), 26), ("Bob".to_owned(), 24)]);
+    }
+
+    #[test]
+    fn birthday_arg_replaces_household() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "[[person]]\nname = \"Alice\"\nbirthyear = \"1998\"\n").unwrap();
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--birthyear", "2000"]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_file_layer(file.path())
+            .stack_args_layer(&args)
+            .unwrap();
+        assert!(!builder.has_people());
+    }
+
+    #[test]
+    fn at_matches_date() {
+        let age_with = |flag: &str| {
+            let args = Args::parse_from([
+                THIS_PROGRAM_NAME,
+                "--birthday",
+                "03/15/1998",
+                flag,
+                "03/14/2024",
+            ]);
+            LayeredAppConfigBuilder::new()
+                .stack_args_layer(&args)
+                .unwrap()
+                .build()
+                .unwrap()
+                .calculate()
+                .age
+        };
+        assert_eq!(age_with("--at"), 25);
+        assert_eq!(age_with("--at"), age_with("--date"));
+    }
+
+    #[test]
+    fn quiet_arg_overrides_config_verbosity() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "verbosity = \"verbose\"\nbirthyear = \"1998\"\n").unwrap();
+
+        let builder = LayeredAppConfigBuilder::new().stack_file_layer(file.path());
+        assert_eq!(builder.verbosity, Verbosity::Verbose);
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--quiet"]);
+        let builder = builder.stack_args_layer(&args).unwrap();
+        assert_eq!(builder.verbosity, Verbosity::Quiet);
+    }
+
+    #[test]
+    fn env_layer_between_file_and_args() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "birthday = \"01/01/1990\"").unwrap();
+        let no_birthday_args = Args::parse_from([THIS_PROGRAM_NAME, "--date", "2024-06-01"]);
+        let birthday_args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--date",
+            "2024-06-01",
+            "--birthday",
+            "2000-01-01",
+        ]);
+        let age = |args: &Args| {
+            LayeredAppConfigBuilder::new()
+                .stack_file_layer(file.path())
+                .stack_env_layer()?
+                .stack_args_layer(args)?
+                .build()
+                .map(|app| app.calculate().age)
+        };
+
+        // The environment is process-wide, so every case lives in this one test
+        std::env::remove_var(BIRTHDAY_ENV_VAR);
+        std::env::remove_var(FORMAT_ENV_VAR);
+        assert_eq!(age(&no_birthday_args).unwrap(), 34);
+
+        std::env::set_var(BIRTHDAY_ENV_VAR, "1995.01.01");
+        std::env::set_var(FORMAT_ENV_VAR, "YMD.");
+        assert_eq!(age(&no_birthday_args).unwrap(), 29);
+        assert_eq!(age(&birthday_args).unwrap(), 24);
+
+        std::env::set_var(FORMAT_ENV_VAR, "XYZ.");
+        let err = age(&no_birthday_args).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid HOWOLDAMI_FORMAT 'XYZ.': Invalid date format"
+        );
+
+        std::env::remove_var(BIRTHDAY_ENV_VAR);
+        std::env::remove_var(FORMAT_ENV_VAR);
+    }
+
+    #[test]
+    fn verbose_sentence() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            verbosity: Verbosity::Verbose,
+            ..Default::default()
+        };
+        let report = app.calculate();
+        assert_eq!(
+            report.messages,
+            vec!["You are 26 years old (born 1998-01-01, as of 2024-01-01)."]
+        );
+        assert_eq!(report.age, 26);
+    }
+
+    #[test]
+    fn age_in_hours_with_birth_time() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "1998-01-01 13:45",
+            "--date",
+            "1998-01-03",
+            "--unit",
+            "hours",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .unit(args.unit)
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(app.birth_time, NaiveTime::from_hms_opt(13, 45, 0).unwrap());
+        assert_eq!(app.calculate().age, 34);
+    }
+
+    #[test]
+    fn age_in_hours_defaults_to_midnight() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(1998, 1, 3).unwrap(),
+            current_time: NaiveTime::from_hms_opt(6, 30, 0).unwrap(),
+            unit: Unit::Hours,
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().age, 54);
+    }
+
+    #[test]
+    fn age_in_seconds() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            unit: Unit::Seconds,
+            ..Default::default()
+        };
+        // 9496 days, past what an i32 can hold
+        assert_eq!(app.calculate().age, 820_454_400);
+
+        app.current_date = NaiveDate::from_ymd_opt(1998, 1, 2).unwrap();
+        app.birth_time = NaiveTime::from_hms_opt(13, 45, 30).unwrap();
+        app.current_time = NaiveTime::from_hms_opt(0, 0, 1).unwrap();
+        assert_eq!(app.calculate().age, 10 * 60 * 60 + 14 * 60 + 31);
+    }
+
+    #[test]
+    fn birth_time_with_configured_format() {
+        let format = DateFormat::default().as_fmt_string();
+        let birthday = DateSpecifier::full("01/02/1998 08:05:30");
+        assert_eq!(
+            birthday.to_naive_date(&format).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 1, 2).unwrap()
+        );
+        assert_eq!(birthday.time(), NaiveTime::from_hms_opt(8, 5, 30));
+    }
+
+    #[test]
+    fn explicit_config_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "birthday = \"01/01/1998\"").unwrap();
+
+        let config = file.path().to_str().unwrap();
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--config", config, "--year", "2024"]);
+        let age = LayeredAppConfigBuilder::new()
+            .birthday_file(None)
+            .stack_config_layer(&args)
+            .unwrap()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap()
+            .calculate()
+            .age;
+        assert_eq!(age, 26);
+    }
+
+    #[test]
+    fn config_layer_reads_the_injected_birthday_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let birthday_file = dir.path().join(".birthday");
+        std::fs::write(&birthday_file, "1998-01-01\n").unwrap();
+        let config = dir.path().join("config.toml");
+        std::fs::write(&config, "").unwrap();
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--config", config.to_str().unwrap()]);
+        let builder = LayeredAppConfigBuilder::new()
+            .birthday_file(Some(birthday_file))
+            .stack_config_layer(&args)
+            .unwrap();
+        assert_eq!(builder.provenance.birthday, Layer::BirthdayFile);
+    }
+
+    #[test]
+    fn missing_explicit_config_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("missing.toml");
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--config", config.to_str().unwrap()]);
+        assert!(LayeredAppConfigBuilder::new()
+            .birthday_file(None)
+            .stack_config_layer(&args)
+            .is_err());
+    }
+
+    #[test]
+    fn missing_birthday_exit_code() {
+        let err = LayeredAppConfigBuilder::new().build().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ConfigError>(),
+            Some(&ConfigError::MissingBirthday)
+        );
+        assert_eq!(exit_code(&err), EXIT_MISSING_BIRTHDAY);
+    }
+
+    #[test]
+    fn chinese_zodiac_respects_verbosity() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            zodiac: Some(Zodiac::Chinese),
+            ..Default::default()
+        };
+        assert_eq!(
+            app.calculate().extras,
+            vec!["Your Chinese zodiac animal is the Tiger."]
+        );
+
+        app.verbosity = Verbosity::Verbose;
+        assert_eq!(
+            app.calculate().extras,
+            vec![
+                "Your Chinese zodiac animal is the Tiger.",
+                "If you were born before Chinese New Year in 1998, you were born in the year of the Ox instead.",
+            ]
+        );
+
+        app.verbosity = Verbosity::Quiet;
+        assert!(app.calculate().extras.is_empty());
+    }
+
+    #[test]
+    fn western_zodiac_suppressed_when_quiet() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 3, 25).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            zodiac: Some(Zodiac::Western),
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().extras, vec!["Your star sign is Aries."]);
+
+        app.verbosity = Verbosity::Quiet;
+        assert!(app.calculate().extras.is_empty());
+    }
+
+    #[test]
+    fn birthday_from_reader() {
+        let input = std::io::Cursor::new("01/02/1998\n");
+        let mut builder = LayeredAppConfigBuilder::new()
+            .stack_reader_layer(input)
+            .unwrap();
+        builder.current_date = Some(DateSpecifier::full("2024-06-01"));
+        let app = builder.build().unwrap();
+        assert_eq!(app.birthday, NaiveDate::from_ymd_opt(1998, 1, 2).unwrap());
+    }
+
+    #[test]
+    fn empty_reader_errors() {
+        let input = std::io::Cursor::new("\n");
+        let err = LayeredAppConfigBuilder::new()
+            .stack_reader_layer(input)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "No birthday found on stdin");
+    }
+
+    #[test]
+    fn two_digit_years() {
+        let options = ParseOptions::new("%Y-%m-%d");
+        let year = |year: &str| {
+            DateSpecifier::year(year)
+                .to_naive_date_with(&options)
+                .unwrap()
+                .year()
+        };
+        assert_eq!(year("98"), 1998);
+        assert_eq!(year("05"), 2005);
+        assert_eq!(year("30"), 1930);
+        assert_eq!(year("29"), 2029);
+        assert_eq!(year("1998"), 1998);
+    }
+
+    #[test]
+    fn custom_century_pivot() {
+        let mut builder = LayeredAppConfigBuilder::new().century_pivot(0);
+        builder.birthday = Some(DateSpecifier::year("05"));
+        builder.current_date = Some(DateSpecifier::year("2024"));
+        assert_eq!(builder.build().unwrap().birthday.year(), 1905);
+    }
+
+    #[test]
+    fn config_template_is_valid() {
+        let config = ConfigFile::parse(CONFIG_TEMPLATE).unwrap();
+        assert_eq!(config.birthday.as_deref(), Some("01/01/1998"));
+        assert!(matches!(
+            config.format,
+            Some(DateFormat::MDY { separator }) if separator == "/"
+        ));
+        assert_eq!(config.verbosity, Some(Verbosity::Normal));
+        assert_eq!(config.person.len(), 2);
+        assert!(config.unknown_keys.is_empty());
+    }
+
+    #[test]
+    fn unknown_config_keys_are_recorded() {
+        let config = ConfigFile::parse(
+            r#"
+            birthdy = "1998-01-01"
+            verbosity = "verbose"
+
+            [[person]]
+            name = "Alice"
+            birthday = "1990-05-17"
+            nickname = "Al"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.birthday, None);
+        assert_eq!(config.unknown_keys, vec!["birthdy", "person[0].nickname"]);
+    }
+
+    #[test]
+    fn custom_greeting() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            wish_happy_birthday: true,
+            greeting: Some("Happy {age}th birthday, {name}!".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().messages, vec!["Happy 26th birthday, you!"]);
+
+        app.name = Some("Alice".to_owned());
+        assert_eq!(
+            app.calculate().messages,
+            vec!["Happy 26th birthday, Alice!"]
+        );
+    }
+
+    #[test]
+    fn greeting_arg_overrides_config() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "greeting = \"From the file\"").unwrap();
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--greeting", "Turning {age}!"]);
+        let builder = LayeredAppConfigBuilder::new().stack_file_layer(file.path());
+        assert_eq!(builder.greeting.as_deref(), Some("From the file"));
+
+        let builder = builder.stack_args_layer(&args).unwrap();
+        assert_eq!(builder.greeting.as_deref(), Some("Turning {age}!"));
+    }
+
+    #[test]
+    fn batch_line_error_json() {
+        let input = std::io::Cursor::new("01/01/1998\nnot a date\n");
+        let mut builder = LayeredAppConfigBuilder::new();
+        builder.current_date = Some(DateSpecifier::full("2024-06-01"));
+        let lines = builder.build_batch(input).unwrap();
+        assert_eq!(lines[0].error_json(), None);
+
+        let error: serde_json::Value =
+            serde_json::from_str(&lines[1].error_json().unwrap()).unwrap();
+        assert_eq!(error["line"], 2);
+        assert_eq!(error["input"], "not a date");
+        assert_eq!(error["kind"], "error");
+    }
+
+    #[test]
+    fn batch_skips_bad_lines() {
+        let input = std::io::Cursor::new("01/01/1998\nnot a date\n\n2000-06-15\n");
+        let mut builder = LayeredAppConfigBuilder::new();
+        builder.current_date = Some(DateSpecifier::full("2024-06-01"));
+        let lines = builder.build_batch(input).unwrap();
+
+        let results: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let age = line.app.as_ref().ok().map(|app| app.calculate().age);
+                (line.line_number, line.input.as_str(), age)
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                (1, "01/01/1998", Some(26)),
+                (2, "not a date", None),
+                (4, "2000-06-15", Some(23)),
+            ]
+        );
+    }
+
+    #[test]
+    fn locale_formats() {
+        let format = |tag| DateFormat::for_locale(tag).map(|format| format.as_fmt_string());
+        assert_eq!(format("en-US").as_deref(), Some("%m/%d/%Y"));
+        assert_eq!(format("en-GB").as_deref(), Some("%d/%m/%Y"));
+        assert_eq!(format("ja-JP").as_deref(), Some("%Y/%m/%d"));
+        assert_eq!(format("de_DE").as_deref(), Some("%d.%m.%Y"));
+        assert_eq!(format("zh-Hans-CN").as_deref(), Some("%Y-%m-%d"));
+        assert_eq!(format("fr").as_deref(), Some("%d/%m/%Y"));
+        assert_eq!(format("xx-YY"), None);
+    }
+
+    #[test]
+    fn format_arg_overrides_locale() {
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--locale", "en-GB"]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap();
+        assert_eq!(builder.format.as_fmt_string(), "%d/%m/%Y");
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--locale", "en-GB", "-f", "YMD-"]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap();
+        assert_eq!(builder.format.as_fmt_string(), "%Y-%m-%d");
+    }
+
+    #[test]
+    fn decimal_age() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 7, 13).unwrap(),
+            ..Default::default()
+        };
+        // 9690 days / 365.25
+        let expected = 26.529774;
+        assert!((app.decimal_age() - expected).abs() < 1e-6);
+        assert_eq!(format!("{:.2}", app.decimal_age()), "26.53");
+    }
+
+    #[test]
+    fn rounding_modes() {
+        let age = 26.529774;
+        assert_eq!(Rounding::Floor.format(age, 2), "26.52");
+        assert_eq!(Rounding::Ceil.format(age, 2), "26.53");
+        assert_eq!(Rounding::Nearest.format(age, 2), "26.53");
+        assert_eq!(Rounding::Floor.format(age, 1), "26.5");
+        assert_eq!(Rounding::Ceil.format(age, 1), "26.6");
+        assert_eq!(Rounding::Nearest.format(age, 1), "26.5");
+        assert_eq!(Rounding::Ceil.format(age, 0), "27");
+    }
+
+    #[test]
+    fn birthday_file_is_a_last_resort() {
+        let mut birthday_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(birthday_file, "1990-06-15").unwrap();
+        let mut config = tempfile::NamedTempFile::new().unwrap();
+        writeln!(config, "birthday = \"2000-01-01\"").unwrap();
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--at", "2024-06-01"]);
+        let age = |builder: LayeredAppConfigBuilder| {
+            builder
+                .stack_args_layer(&args)
+                .unwrap()
+                .build()
+                .unwrap()
+                .calculate()
+                .age
+        };
+
+        let builder =
+            LayeredAppConfigBuilder::new().stack_birthday_file_layer(birthday_file.path());
+        assert_eq!(builder.provenance.birthday, Layer::BirthdayFile);
+        assert_eq!(age(builder), 33);
+
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_birthday_file_layer(birthday_file.path())
+            .stack_file_layer(config.path());
+        assert_eq!(age(builder), 24);
+
+        let missing = birthday_file.path().with_extension("missing");
+        let builder = LayeredAppConfigBuilder::new().stack_birthday_file_layer(&missing);
+        assert!(builder.birthday.is_none());
+    }
+
+    #[test]
+    fn prompt_fills_in_a_missing_birthday() {
+        let mut prompt = Vec::new();
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_prompt_layer(std::io::Cursor::new("1998-01-01\n"), &mut prompt)
+            .unwrap();
+        assert_eq!(prompt, b"When is your birthday? ");
+        assert_eq!(builder.birthday, Some(DateSpecifier::full("1998-01-01")));
+        assert_eq!(builder.provenance.birthday, Layer::Prompt);
+
+        // Nothing is asked when a birthday is already known
+        let mut prompt = Vec::new();
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--birthday", "2000-06-15"]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .stack_prompt_layer(std::io::Cursor::new("1998-01-01\n"), &mut prompt)
+            .unwrap();
+        assert!(prompt.is_empty());
+        assert_eq!(builder.birthday, Some(DateSpecifier::full("2000-06-15")));
+
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_prompt_layer(std::io::Cursor::new("\n"), &mut Vec::new())
+            .unwrap();
+        assert!(builder.build().unwrap_err().is::<ConfigError>());
+    }
+
+    #[test]
+    fn explain_attributes_layers() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "format = {{ YMD = {{ separator = \"-\" }} }}").unwrap();
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--birthday", "1998-01-01"]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_file_layer(file.path())
+            .stack_args_layer(&args)
+            .unwrap();
+        assert_eq!(
+            builder.explain(),
+            vec![
+                "birthday: 1998-01-01 (from the command line)",
+                "format: YMD- (from the config file)",
+                "current_date: today (from the system clock)",
+            ]
+        );
+    }
+
+    #[test]
+    fn build_rejects_future_birthday() {
+        let mut builder = LayeredAppConfigBuilder::new();
+        builder.birthday = Some(DateSpecifier::full("2030-01-01"));
+        builder.current_date = Some(DateSpecifier::full("2024-01-01"));
+        let err = builder.build().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("birthday 2030-01-01 is after current date 2024-01-01"));
+
+        let builder = builder.allow_future(true);
+        assert_eq!(builder.build().unwrap().calculate().age, -6);
+    }
+
+    #[test]
+    fn build_rejects_future_since() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--since",
+            "2030-01-01",
+            "--at",
+            "2024-01-01",
+        ]);
+        let err = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The --since date is after the current date"
+        );
+    }
+
+    #[test]
+    fn sleeps_until_birthday() {
+        let sleeps = |month, day| {
+            let app = App {
+                birthday: NaiveDate::from_ymd_opt(2018, 6, 15).unwrap(),
+                current_date: NaiveDate::from_ymd_opt(2024, month, day).unwrap(),
+                sleeps: true,
+                ..Default::default()
+            };
+            app.calculate().extras
+        };
+        assert_eq!(sleeps(6, 3), vec!["12 sleeps until your birthday!"]);
+        assert_eq!(sleeps(6, 14), vec!["1 sleep until your birthday!"]);
+        assert_eq!(sleeps(6, 15), vec!["Happy birthday! 0 sleeps!"]);
+        assert_eq!(sleeps(6, 16), vec!["364 sleeps until your birthday!"]);
+    }
+
+    #[test]
+    fn wall_clock_straddles_midnight() {
+        let instant = Utc.with_ymd_and_hms(2024, 1, 1, 23, 30, 0).unwrap();
+        let ahead = chrono::FixedOffset::east_opt(2 * 60 * 60).unwrap();
+        assert_eq!(
+            wall_clock(instant, &Utc).date(),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+        assert_eq!(
+            wall_clock(instant, &ahead).date(),
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_local_around_dst() {
+        let local = |date: &str, hour, minute| {
+            date.parse::<NaiveDate>()
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+        };
+        let new_york = chrono_tz::America::New_York;
+        // 01:30 happens twice when clocks fall back; the first is still EDT
+        let ambiguous = resolve_local(&new_york, local("2024-11-03", 1, 30)).unwrap();
+        assert_eq!(ambiguous.naive_utc(), local("2024-11-03", 5, 30));
+        // 02:30 is skipped when clocks spring forward, and reads as 03:30 EDT
+        let skipped = resolve_local(&new_york, local("2024-03-10", 2, 30)).unwrap();
+        assert_eq!(skipped.naive_local(), local("2024-03-10", 3, 30));
+
+        // Santiago skips midnight itself, which still falls on the same date
+        let santiago = chrono_tz::America::Santiago;
+        let midnight = resolve_local(&santiago, local("2024-09-08", 0, 0)).unwrap();
+        assert_eq!(midnight.date_naive(), "2024-09-08".parse().unwrap());
+        assert_eq!(midnight.naive_local(), local("2024-09-08", 1, 0));
+    }
+
+    #[test]
+    fn hours_across_dst_in_timezone() {
+        let app = |timezone| App {
+            birthday: NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+            current_time: NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+            timezone,
+            ..Default::default()
+        };
+        assert_eq!(app(None).age_in(Unit::Hours), 4);
+        assert_eq!(
+            app(Some(chrono_tz::America::New_York)).age_in(Unit::Hours),
+            3
+        );
+    }
+
+    #[test]
+    fn utc_today_uses_injected_clock() {
+        let instant = Utc.with_ymd_and_hms(2024, 1, 1, 23, 30, 0).unwrap();
+        let mut builder = LayeredAppConfigBuilder::new()
+            .utc(true)
+            .clock(FixedClock(instant));
+        builder.birthday = Some(DateSpecifier::full("1998-01-02"));
+        let app = builder.build().unwrap();
+        assert_eq!(
+            app.current_date,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+        assert_eq!(app.calculate().age, 25);
+    }
+
+    #[test]
+    fn timezone_today_uses_injected_clock() {
+        let instant = Utc.with_ymd_and_hms(2024, 1, 1, 20, 0, 0).unwrap();
+        let today_in = |timezone| {
+            let mut builder = LayeredAppConfigBuilder::new()
+                .timezone(Some(timezone))
+                .clock(FixedClock(instant));
+            builder.birthday = Some(DateSpecifier::full("1998-01-02"));
+            builder.build().unwrap().current_date
+        };
+        assert_eq!(
+            today_in(chrono_tz::America::New_York),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+        assert_eq!(
+            today_in(chrono_tz::Asia::Tokyo),
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_timezone_errors() {
+        let err =
+            Args::try_parse_from([THIS_PROGRAM_NAME, "--timezone", "Mars/Olympus"]).unwrap_err();
+        assert!(err.to_string().contains("Mars/Olympus"));
+    }
+
+    #[test]
+    fn today_comes_from_clock() {
+        let instant = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+        let clock = FixedClock(instant);
+        let mut builder = LayeredAppConfigBuilder::new().clock(FixedClock(instant));
+        builder.birthday = Some(DateSpecifier::full("1998-03-16"));
+        let app = builder.build().unwrap();
+        assert_eq!(app.current_date, clock.today());
+        assert_eq!(app.current_time, wall_clock(instant, &Local).time());
+    }
+
+    #[test]
+    fn half_birthday_clamps_month_end() {
+        let birthday = NaiveDate::from_ymd_opt(1998, 8, 31).unwrap();
+        assert_eq!(
+            half_birthday_in(birthday, 2023),
+            NaiveDate::from_ymd_opt(2023, 2, 28)
+        );
+        assert_eq!(
+            half_birthday_in(birthday, 2024),
+            NaiveDate::from_ymd_opt(2024, 2, 29)
+        );
+
+        let birthday = NaiveDate::from_ymd_opt(1998, 12, 31).unwrap();
+        assert_eq!(
+            half_birthday_in(birthday, 2024),
+            NaiveDate::from_ymd_opt(2024, 6, 30)
+        );
+    }
+
+    #[test]
+    fn next_half_birthday_message() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 8, 31).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            half_birthday: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            app.calculate().extras,
+            vec!["Your next half-birthday is 2025-02-28, 364 days from now"]
+        );
+
+        app.current_date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(app.calculate().extras, vec!["Today is your half-birthday!"]);
+    }
+
+    #[test]
+    fn render_template() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(1999, 1, 11).unwrap(),
+            unit: Unit::Weeks,
+            ..Default::default()
+        };
+        assert_eq!(
+            app.render_template("{years}y ({days} days, {age} weeks) from {birthday} to {today}")
+                .unwrap(),
+            "1y (375 days, 53 weeks) from 1998-01-01 to 1999-01-11"
+        );
+    }
+
+    #[test]
+    fn template_dates_follow_output_date_format() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 31).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            output_date_format: Some("%d.%m.%Y".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            app.render_template("{birthday} to {today}").unwrap(),
+            "31.01.1998 to 01.06.2024"
+        );
+    }
+
+    #[test]
+    fn template_errors() {
+        let app = App::default();
+        let err = app.render_template("{age} {nope}").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown placeholder {nope} in template");
+        assert!(app.render_template("{age").is_err());
+    }
+
+    #[test]
+    fn multi_character_separator() {
+        let format: DateFormat = "YMD, ".parse().unwrap();
+        assert_eq!(format.as_fmt_string(), "%Y, %m, %d");
+        let date = DateSpecifier::full("1998, 01, 31")
+            .to_naive_date(&format.as_fmt_string())
+            .unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1998, 1, 31).unwrap());
+
+        let format: DateFormat = "DMY%%".parse().unwrap();
+        assert_eq!(format.as_fmt_string(), "%d%%%%%m%%%%%Y");
+        let date = DateSpecifier::full("31%%01%%1998")
+            .to_naive_date(&format.as_fmt_string())
+            .unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1998, 1, 31).unwrap());
+    }
+
+    #[test]
+    fn display_matches_format_flag() {
+        for input in ["MDY/", "YMD, ", "DMY%%"] {
+            let format: DateFormat = input.parse().unwrap();
+            assert_eq!(format.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn empty_separator_errors() {
+        assert_eq!(
+            "YMD".parse::<DateFormat>().unwrap_err(),
+            "No separator found"
+        );
+    }
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(1996));
+        assert!(!is_leap_year(1998));
+        assert!(!is_leap_year(1900));
+        assert!(is_leap_year(2000));
+    }
+
+    #[test]
+    fn born_on_leap_year_message() {
+        let app = |year, verbosity| App {
+            birthday: NaiveDate::from_ymd_opt(year, 3, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            verbosity,
+            born_on_leap_year: true,
+            ..Default::default()
+        };
+        let message = "You were born in a leap year.".to_owned();
+        assert_eq!(
+            app(1996, Verbosity::Normal).calculate().extras,
+            vec![message]
+        );
+        assert!(app(1998, Verbosity::Normal).calculate().extras.is_empty());
+        assert!(app(1996, Verbosity::Quiet).calculate().extras.is_empty());
+    }
+
+    #[test]
+    fn milestone_dates() {
+        let birthday = NaiveDate::from_ymd_opt(1998, 1, 31).unwrap();
+        assert_eq!(
+            milestone_date(birthday, 30),
+            NaiveDate::from_ymd_opt(2028, 1, 31)
+        );
+        let leap_day = NaiveDate::from_ymd_opt(1996, 2, 29).unwrap();
+        assert_eq!(
+            milestone_date(leap_day, 21),
+            NaiveDate::from_ymd_opt(2017, 2, 28)
+        );
+        assert_eq!(
+            milestone_date(leap_day, 40),
+            NaiveDate::from_ymd_opt(2036, 2, 29)
+        );
+    }
+
+    #[test]
+    fn anniversaries_across_years() {
+        let date = |date: &str| date.parse::<NaiveDate>().unwrap();
+        let ages = |birthday, start, end| {
+            anniversaries_between(date(birthday), date(start), date(end))
+                .into_iter()
+                .map(|anniversary| (anniversary.age, anniversary.date.to_string()))
+                .collect::<Vec<_>>()
+        };
+        // Both ends are inclusive, and a range ending the day before a birthday skips it
+        assert_eq!(
+            ages("1990-06-15", "2020-06-15", "2023-06-14"),
+            [
+                (30, "2020-06-15".to_owned()),
+                (31, "2021-06-15".to_owned()),
+                (32, "2022-06-15".to_owned()),
+            ]
+        );
+        assert_eq!(
+            ages("2000-02-29", "2000-01-01", "2004-12-31"),
+            [
+                (1, "2001-02-28".to_owned()),
+                (2, "2002-02-28".to_owned()),
+                (3, "2003-02-28".to_owned()),
+                (4, "2004-02-29".to_owned()),
+            ]
+        );
+        assert_eq!(ages("1990-06-15", "2020-06-16", "2021-06-14"), []);
+        assert_eq!(ages("1990-06-15", "1980-01-01", "1990-06-15"), []);
+    }
+
+    #[test]
+    fn milestone_messages() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1960, 6, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            milestones: Some(vec![]),
+            ..Default::default()
+        };
+        assert_eq!(
+            app.calculate().extras,
+            vec![
+                "You turn 70 on 2030-06-01.",
+                "You turn 80 on 2040-06-01.",
+                "You turn 90 on 2050-06-01.",
+                "You turn 100 on 2060-06-01.",
+            ]
+        );
+
+        app.milestones = Some(vec![18, 65, 67]);
+        assert_eq!(
+            app.calculate().extras,
+            vec!["You turn 65 on 2025-06-01.", "You turn 67 on 2027-06-01."]
+        );
+    }
+
+    #[test]
+    fn retirement() {
+        let extras = |birthday: &str, retire_at| {
+            App {
+                birthday: birthday.parse().unwrap(),
+                current_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+                retire_at: Some(retire_at),
+                ..Default::default()
+            }
+            .calculate()
+            .extras
+        };
+        assert_eq!(
+            extras("1990-06-01", 65),
+            ["You can retire on 2055-06-01, in 31 years (11322 days)"]
+        );
+        assert_eq!(
+            extras("1950-01-01", 65),
+            ["You could have retired 9 years ago."]
+        );
+        assert_eq!(
+            extras("1959-01-01", 65),
+            ["You could have retired 152 days ago."]
+        );
+        assert_eq!(extras("1959-06-01", 65), ["You can retire today!"]);
+        // Feb 29 birthdays reach the age on Feb 28 in non-leap years
+        assert_eq!(
+            extras("1960-02-29", 67),
+            ["You can retire on 2027-02-28, in 2 years (1002 days)"]
+        );
+    }
+
+    #[test]
+    fn retire_at_defaults_to_65() {
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--retire-at"]);
+        assert_eq!(args.retire_at, Some(65));
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--retire-at", "67"]);
+        assert_eq!(args.retire_at, Some(67));
+    }
+
+    #[test]
+    fn output_date_format() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "1960-06-01",
+            "--at",
+            "2024-01-01",
+            "--output-date-format",
+            "DMY.",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .milestones(Some(vec![70]))
+            .retire_at(Some(65))
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.calculate().extras,
+            [
+                "You turn 70 on 01.06.2030.",
+                "You can retire on 01.06.2025, in 1 year (517 days)",
+            ]
+        );
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--output-date-format", "XYZ/"]);
+        assert!(LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .is_err());
+    }
+
+    #[test]
+    fn elapsed_between_historical_dates() {
+        let moon_landing = NaiveDate::from_ymd_opt(1969, 7, 20).unwrap();
+        assert_eq!(
+            Elapsed::between(moon_landing, NaiveDate::from_ymd_opt(2019, 7, 20).unwrap()),
+            Some(Elapsed {
+                years: 50,
+                days: 18262
+            })
+        );
+
+        let independence = NaiveDate::from_ymd_opt(1776, 7, 4).unwrap();
+        let wall_falls = NaiveDate::from_ymd_opt(1989, 11, 9).unwrap();
+        let elapsed = Elapsed::between(independence, wall_falls).unwrap();
+        assert_eq!(elapsed.to_string(), "213 years (77924 days)");
+        assert_eq!(Elapsed::between(wall_falls, independence), None);
+    }
+
+    #[test]
+    fn since_replaces_birthday() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--since",
+            "1969-07-20",
+            "--at",
+            "2019-07-21",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.elapsed(),
+            Some(Elapsed {
+                years: 50,
+                days: 18263
+            })
+        );
+    }
+
+    #[test]
+    fn iso_durations() {
+        let report = |birthday: &str, on: &str| {
+            App {
+                birthday: birthday.parse().unwrap(),
+                current_date: on.parse().unwrap(),
+                ..Default::default()
+            }
+            .calculate()
+        };
+        let report_of = report("1998-01-01", "2024-04-15");
+        let breakdown = Breakdown::between(report_of.birthday, report_of.current_date).unwrap();
+        assert_eq!(
+            report_of.iso_duration(false),
+            format!(
+                "P{}Y{}M{}D",
+                breakdown.years, breakdown.months, breakdown.days
+            )
+        );
+        assert_eq!(report_of.iso_duration(false), "P26Y3M14D");
+
+        let whole_years = report("1998-01-01", "2024-01-01");
+        assert_eq!(whole_years.iso_duration(false), "P26Y");
+        assert_eq!(whole_years.iso_duration(true), "P26Y0M0D");
+        assert_eq!(
+            report("2024-01-01", "2024-01-01").iso_duration(false),
+            "P0D"
+        );
+        assert_eq!(
+            report("2024-03-01", "2024-01-01").iso_duration(false),
+            "-P2M"
+        );
+    }
+
+    #[test]
+    fn age_gaps() {
+        let gap = |a: &str, b: &str| {
+            AgeGap::between(
+                a.parse::<NaiveDate>().unwrap(),
+                b.parse::<NaiveDate>().unwrap(),
+            )
+            .unwrap()
+            .to_string()
+        };
+        assert_eq!(
+            gap("1990-01-01", "1993-02-12"),
+            "You are 3 years, 42 days older"
+        );
+        assert_eq!(
+            gap("1993-02-12", "1990-01-01"),
+            "You are 3 years, 42 days younger"
+        );
+        assert_eq!(
+            gap("2001-05-05", "2001-05-05"),
+            "You were born on the same day"
+        );
+        // Twins a year apart share a birthday but not an age
+        assert_eq!(
+            gap("2000-05-05", "2001-05-05"),
+            "You are 1 year, 0 days older"
+        );
+    }
+
+    #[test]
+    fn other_birthday_uses_format() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "01/02/1990",
+            "--other-birthday",
+            "12/31/1989",
+            "--format",
+            "MDY/",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .other_birthday(args.other_birthday.clone())
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.age_gap(),
+            Some(AgeGap {
+                years: 0,
+                days: 2,
+                older: false
+            })
+        );
+    }
+
+    #[test]
+    fn bash_completions() {
+        let mut script = Vec::new();
+        write_completions(clap_complete::Shell::Bash, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("--birthday"));
+    }
+
+    #[test]
+    fn year_progress_through_age() {
+        let birthday = NaiveDate::from_ymd_opt(1998, 1, 1).unwrap();
+        let on = |month, day| NaiveDate::from_ymd_opt(2023, month, day).unwrap();
+        assert_eq!(year_progress(birthday, on(1, 1)), Some((0, 26)));
+        assert_eq!(year_progress(birthday, on(7, 2)), Some((49, 26)));
+        assert_eq!(year_progress(birthday, on(12, 31)), Some((99, 26)));
+
+        let leap_day = NaiveDate::from_ymd_opt(1996, 2, 29).unwrap();
+        assert_eq!(year_progress(leap_day, on(2, 28)), Some((0, 28)));
+        assert_eq!(
+            year_progress(birthday, NaiveDate::from_ymd_opt(1997, 1, 1).unwrap()),
+            None
+        );
+
+        let app = App {
+            birthday,
+            current_date: on(9, 24),
+            year_progress: true,
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().extras, vec!["72% of the way to 26"]);
+    }
+
+    #[test]
+    fn age_bounds() {
+        assert!(within_bounds(18, Some(18), None));
+        assert!(!within_bounds(17, Some(18), None));
+        assert!(within_bounds(65, None, Some(65)));
+        assert!(!within_bounds(66, None, Some(65)));
+        assert!(within_bounds(30, Some(18), Some(65)));
+        assert!(!within_bounds(70, Some(18), Some(65)));
+        assert!(within_bounds(-1, None, None));
+    }
+
+    #[test]
+    fn ordinal_dates() {
+        let parse = |date| DateSpecifier::full(date).to_naive_date("%m/%d/%Y");
+        assert_eq!(
+            parse("1998-001").unwrap(),
+            NaiveDate::from_ymd_opt(1998, 1, 1).unwrap()
+        );
+        assert_eq!(
+            parse("1998-032").unwrap(),
+            NaiveDate::from_ymd_opt(1998, 2, 1).unwrap()
+        );
+        assert_eq!(
+            parse("1998-060").unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 1).unwrap()
+        );
+        assert_eq!(
+            parse("1996-060").unwrap(),
+            NaiveDate::from_ymd_opt(1996, 2, 29).unwrap()
+        );
+        assert!(parse("1996-366").is_ok());
+        assert!(parse("1998-366").is_err());
+        assert!(parse("1998-000").is_err());
+    }
+
+    #[test]
+    fn colored_greeting() {
+        let mut app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            wish_happy_birthday: true,
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().messages, vec![DEFAULT_GREETING]);
+
+        app.color = true;
+        assert_eq!(
+            app.calculate().messages,
+            vec![format!("\x1b[95m{}\x1b[39m", DEFAULT_GREETING)]
+        );
+        assert_eq!(style_age("26", true), "\x1b[1m26\x1b[0m");
+        assert_eq!(style_age("26", false), "26");
+    }
+
+    #[test]
+    fn next_milestone_ages() {
+        assert_eq!(next_milestone_age(29, 10), 30);
+        assert_eq!(next_milestone_age(30, 10), 40);
+        assert_eq!(next_milestone_age(64, 10), 70);
+        assert_eq!(next_milestone_age(64, 5), 65);
+        assert_eq!(next_milestone_age(0, 18), 18);
+    }
+
+    #[test]
+    fn next_milestone_message() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1994, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            next_milestone: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            app.calculate().extras,
+            vec!["You turn 40 on 2034-01-01, 3653 days from now"]
+        );
+    }
+
+    #[test]
+    fn birthday_and_birthyear_args_conflict() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "1998-01-01",
+            "--birthyear",
+            "1998",
+        ]);
+        let err = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap_err();
+        assert!(err.to_string().contains("both give your birthday"));
+    }
+
+    #[cfg(feature = "famous")]
+    #[test]
+    fn famous_and_birthyear_args_conflict() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--famous",
+            "Ada Lovelace",
+            "--birthyear",
+            "1990",
+        ]);
+        let err = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap_err();
+        assert!(err.to_string().contains("both give your birthday"));
+    }
+
+    #[test]
+    fn birthday_arg_overrides_file_birthyear() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "birthyear = \"1990\"").unwrap();
+        let builder = LayeredAppConfigBuilder::new().stack_file_layer(file.path());
+
+        let birthday = DateSpecifier::full("1998-06-01");
+        assert_eq!(
+            builder.birthday_override_note(&birthday, Layer::Args).as_deref(),
+            Some("Using birthday '1998-06-01' from the command line over birthyear '1990' from the config file")
+        );
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--birthday", "1998-06-01"]);
+        let builder = builder.stack_args_layer(&args).unwrap();
+        assert_eq!(builder.birthday, Some(birthday));
+        assert_eq!(builder.provenance.birthday, Layer::Args);
+    }
+
+    #[test]
+    fn birthyear_arg_overrides_file_birthday() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "birthday = \"1998-06-01\"").unwrap();
+        let builder = LayeredAppConfigBuilder::new().stack_file_layer(file.path());
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--birthyear", "1990"]);
+        let builder = builder.stack_args_layer(&args).unwrap();
+        assert_eq!(builder.birthday, Some(DateSpecifier::year("1990")));
+    }
+
+    #[test]
+    fn bce_birthyear() {
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--birthyear", "-44", "--year", "2024"]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(app.birthday, NaiveDate::from_ymd_opt(-44, 1, 1).unwrap());
+        assert_eq!(app.calculate().age, 2068);
+
+        let year_zero = NaiveDate::from_ymd_opt(0, 6, 1).unwrap();
+        assert_eq!(
+            age(year_zero, NaiveDate::from_ymd_opt(1, 5, 31).unwrap()),
+            Some(0)
+        );
+        assert_eq!(
+            age(year_zero, NaiveDate::from_ymd_opt(1, 6, 1).unwrap()),
+            Some(1)
+        );
+        assert_eq!(
+            age(NaiveDate::from_ymd_opt(-1, 1, 1).unwrap(), year_zero),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn show_parsed_dmy() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--format",
+            "DMY/",
+            "--birthday",
+            "05/01/1998",
+            "--at",
+            "04/03/2024",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.parsed_dates(),
+            vec![
+                "Parsed birthday: 1998-01-05",
+                "Parsed current date: 2024-03-04"
+            ]
+        );
+    }
+
+    #[test]
+    fn file_format_parses_arg_birthday() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "format = {{ YMD = {{ separator = \"/\" }} }}").unwrap();
+
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "1998/01/31",
+            "--at",
+            "2024/02/01",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_file_layer(file.path())
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(app.birthday, NaiveDate::from_ymd_opt(1998, 1, 31).unwrap());
+        assert_eq!(app.calculate().age, 26);
+    }
+
+    #[test]
+    fn dog_year_formulas() {
+        assert_eq!(DogYearFormula::Simple.dog_years(1), 7);
+        assert_eq!(DogYearFormula::Simple.dog_years(26), 182);
+        assert_eq!(DogYearFormula::Accurate.dog_years(1), 10);
+        assert_eq!(DogYearFormula::Accurate.dog_years(2), 21);
+        assert_eq!(DogYearFormula::Accurate.dog_years(26), 117);
+
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            dog_years: Some(DogYearFormula::Simple),
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().extras, vec!["In dog years, you are 182."]);
+    }
+
+    #[test]
+    fn porcelain_line() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(1998, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().porcelain(), "26\t1998-01-01\t2024-01-01");
+    }
+
+    #[test]
+    fn weeks_and_days() {
+        let birthday = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let weeks_days = |days| {
+            App {
+                birthday,
+                current_date: birthday + chrono::Duration::days(days),
+                unit: Unit::WeeksDays,
+                ..Default::default()
+            }
+            .calculate()
+            .age_string()
+        };
+        assert_eq!(weeks_days(0), "0 weeks 0 days");
+        assert_eq!(weeks_days(6), "0 weeks 6 days");
+        assert_eq!(weeks_days(7), "1 week 0 days");
+        assert_eq!(weeks_days(59), "8 weeks 3 days");
+    }
+
+    #[test]
+    fn file_pins_current_date() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "birthday = \"1998-06-01\"").unwrap();
+        writeln!(file, "current_date = \"2024-05-31\"").unwrap();
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_file_layer(file.path())
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.current_date,
+            NaiveDate::from_ymd_opt(2024, 5, 31).unwrap()
+        );
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--date", "2024-06-01"]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_file_layer(file.path())
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.current_date,
+            NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+        );
+    }
+
+    const TWO_PROFILES: &str = r#"
+birthday = "2000-01-01"
+
+[profiles.work]
+birthday = "1990-06-15"
+
+[profiles.home]
+birthday = "15.06.1985"
+format = { DMY = { separator = "." } }
+"#;
+
+    #[test]
+    fn profile_selects_birthday_and_format() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "{}", TWO_PROFILES).unwrap();
+        let app = |profile: &str| {
+            let args = Args::parse_from([
+                THIS_PROGRAM_NAME,
+                "--config",
+                file.path().to_str().unwrap(),
+                "--profile",
+                profile,
+                "--at",
+                "16.06.2024",
+            ]);
+            LayeredAppConfigBuilder::new()
+                .birthday_file(None)
+                .profile(args.profile.clone())
+                .stack_config_layer(&args)
+                .unwrap()
+                .stack_args_layer(&args)
+                .unwrap()
+                .build()
+        };
+        assert_eq!(app("home").unwrap().calculate().age, 39);
+        // The work profile keeps the top-level MDY format, which can't read the --at date
+        assert!(app("work").is_err());
+    }
+
+    #[test]
+    fn missing_profile_is_an_error() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "{}", TWO_PROFILES).unwrap();
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--config",
+            file.path().to_str().unwrap(),
+            "--profile",
+            "school",
+        ]);
+        let err = LayeredAppConfigBuilder::new()
+            .birthday_file(None)
+            .profile(args.profile.clone())
+            .stack_config_layer(&args)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No profile named 'school' in the config file"
+        );
+    }
+
+    #[test]
+    fn file_pins_current_year() {
+        let config = ConfigFile::parse("birthyear = \"1998\"\ncurrent_year = \"2024\"\n").unwrap();
+        let app = LayeredAppConfigBuilder::new()
+            .stack_config(config)
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.current_date,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn format_presets() {
+        assert_eq!(FormatPreset::Us.format().as_fmt_string(), "%m/%d/%Y");
+        assert_eq!(FormatPreset::Iso.format().as_fmt_string(), "%Y-%m-%d");
+        assert_eq!(FormatPreset::Euro.format().as_fmt_string(), "%d.%m.%Y");
+
+        let args = Args::try_parse_from([
+            THIS_PROGRAM_NAME,
+            "--format",
+            "MDY/",
+            "--format-preset",
+            "euro",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn config_search_order() {
+        let candidates = config_candidates(
+            Some(PathBuf::from("/xdg")),
+            Some(PathBuf::from("/platform/config.toml")),
+            Some(PathBuf::from("/work")),
+        );
+        assert_eq!(
+            candidates,
+            vec![
+                PathBuf::from("/xdg/howoldami/config.toml"),
+                PathBuf::from("/platform/config.toml"),
+                PathBuf::from("/work/howoldami.toml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn local_config_is_found() {
+        let xdg = tempfile::tempdir().unwrap();
+        let work = tempfile::tempdir().unwrap();
+        std::fs::write(
+            work.path().join("howoldami.toml"),
+            "birthday = \"1998-01-01\"\n",
+        )
+        .unwrap();
+
+        let candidates = config_candidates(
+            Some(xdg.path().to_owned()),
+            None,
+            Some(work.path().to_owned()),
+        );
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_first_file_layer(&candidates)
+            .unwrap();
+        assert_eq!(builder.birthday, Some(DateSpecifier::full("1998-01-01")));
+        assert_eq!(builder.provenance.birthday, Layer::File);
+    }
+
+    #[test]
+    fn missing_and_malformed_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.toml");
+        let err = ConfigFile::from_file(&missing).unwrap_err();
+        assert!(is_not_found(&err));
+
+        let malformed = dir.path().join("malformed.toml");
+        std::fs::write(&malformed, "birthday = 1998-01-01 oops\n").unwrap();
+        let err = ConfigFile::from_file(&malformed).unwrap_err();
+        assert!(!is_not_found(&err));
+
+        // Both are skipped without --strict-config
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_file_layer(&missing)
+            .stack_file_layer(&malformed);
+        assert!(builder.birthday.is_none());
+
+        let candidates = [missing, malformed];
+        assert!(LayeredAppConfigBuilder::new()
+            .stack_first_file_layer(&candidates)
+            .is_ok());
+        let err = LayeredAppConfigBuilder::new()
+            .strict_config(true)
+            .stack_first_file_layer(&candidates)
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Could not read config file"));
+    }
+
+    #[test]
+    fn counting_birthdays() {
+        let birthday = NaiveDate::from_ymd_opt(1998, 6, 15).unwrap();
+        let on = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        assert_eq!(birthdays_celebrated(birthday, on(14)), Some(25));
+        assert_eq!(birthdays_celebrated(birthday, on(15)), Some(26));
+        assert_eq!(birthdays_celebrated(birthday, on(16)), Some(26));
+        assert_eq!(birthdays_celebrated(birthday, birthday), Some(0));
+
+        let leap_day = NaiveDate::from_ymd_opt(1996, 2, 29).unwrap();
+        let feb_28 = NaiveDate::from_ymd_opt(2023, 2, 28).unwrap();
+        assert_eq!(birthdays_celebrated(leap_day, feb_28), Some(27));
+        assert_eq!(age(leap_day, feb_28), Some(27));
+
+        let app = App {
+            birthday,
+            current_date: on(15),
+            count_birthdays: true,
+            ..Default::default()
+        };
+        assert_eq!(app.calculate().extras, vec!["You have had 26 birthdays."]);
+    }
+
+    #[test]
+    fn errors_as_json() {
+        let err = color_eyre::Report::new(ConfigError::MissingBirthday);
+        assert_eq!(
+            error_json(&err),
+            r#"{"error":"No birthday specified in either config or command line args","kind":"missing_birthday"}"#
+        );
+
+        let err = eyre!("Invalid year: x").wrap_err("Could not parse");
+        assert_eq!(
+            error_json(&err),
+            r#"{"error":"Could not parse: Invalid year: x","kind":"error"}"#
+        );
+    }
+
+    #[test]
+    fn month_year_birthday() {
+        let birthday = DateSpecifier::month_year("1998-06");
+        assert_eq!(
+            birthday.to_naive_date("%m/%d/%Y").unwrap(),
+            NaiveDate::from_ymd_opt(1998, 6, 1).unwrap()
+        );
+        assert!(DateSpecifier::month_year("1998-13")
+            .to_naive_date("%m/%d/%Y")
+            .is_err());
+        assert!(DateSpecifier::month_year("1998")
+            .to_naive_date("%m/%d/%Y")
+            .is_err());
+
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthmonth-year",
+            "1998-06",
+            "--at",
+            "2024-06-01",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(app.calculate().age, 26);
+        assert!(!app.wish_happy_birthday);
+    }
+
+    #[test]
+    fn days_alive() {
+        let app = |to| App {
+            birthday: NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+            current_date: to,
+            ..Default::default()
+        };
+        assert_eq!(
+            app(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()).days_alive(),
+            366
+        );
+        assert_eq!(
+            app(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()).days_alive(),
+            1096
+        );
+        assert_eq!(
+            app(NaiveDate::from_ymd_opt(2023, 3, 1).unwrap()).days_alive(),
+            0
+        );
+    }
+
+    #[test]
+    fn historical_year_math_skips_year_zero() {
+        let age = |historical_year_math| {
+            let mut builder =
+                LayeredAppConfigBuilder::new().historical_year_math(historical_year_math);
+            builder.birthday = Some(DateSpecifier::year("-10"));
+            builder.current_date = Some(DateSpecifier::year("0010"));
+            builder.build().map(|app| app.calculate().age)
+        };
+        // Astronomically -10 is 11 BCE, so 20 years pass by 10 CE
+        assert_eq!(age(false).unwrap(), 20);
+        // Historically -10 is 10 BCE, and with no year 0 only 19 pass
+        assert_eq!(age(true).unwrap(), 19);
+
+        let mut builder = LayeredAppConfigBuilder::new().historical_year_math(true);
+        builder.birthday = Some(DateSpecifier::year("0"));
+        builder.current_date = Some(DateSpecifier::year("0010"));
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn julian_dates() {
+        let julian =
+            |y, m, d| Calendar::Julian.to_gregorian(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+        // Protestant Germany followed Feb 18, 1700 (Julian) with Mar 1 (Gregorian)
+        assert_eq!(julian(1700, 2, 18), NaiveDate::from_ymd_opt(1700, 2, 28));
+        assert_eq!(julian(1700, 2, 19), NaiveDate::from_ymd_opt(1700, 3, 1));
+        assert_eq!(julian(1700, 3, 1), NaiveDate::from_ymd_opt(1700, 3, 12));
+        assert_eq!(julian(1582, 10, 4), NaiveDate::from_ymd_opt(1582, 10, 14));
+        assert_eq!(julian(2000, 1, 1), NaiveDate::from_ymd_opt(2000, 1, 14));
+        assert_eq!(
+            Calendar::Gregorian.to_gregorian(NaiveDate::from_ymd_opt(1700, 3, 1).unwrap()),
+            NaiveDate::from_ymd_opt(1700, 3, 1)
+        );
+    }
+
+    #[test]
+    fn life_expectancy_share() {
+        let message = |from, expectancy| {
+            App {
+                birthday: NaiveDate::from_ymd_opt(1984, 1, 1).unwrap(),
+                current_date: NaiveDate::from_ymd_opt(from, 1, 1).unwrap(),
+                life_expectancy: Some(expectancy),
+                ..Default::default()
+            }
+            .calculate()
+            .extras
+        };
+        assert_eq!(
+            message(2024, 80),
+            ["You have lived 50.0% of a life expectancy of 80 years"]
+        );
+        assert_eq!(
+            message(2014, 73),
+            ["You have lived 41.1% of a life expectancy of 73 years"]
+        );
+        assert_eq!(
+            message(2084, 80),
+            ["You have lived 100% of a life expectancy of 80 years, and kept going"]
+        );
+    }
+
+    #[test]
+    fn life_expectancy_default_from_config() {
+        let config =
+            ConfigFile::parse("birthday = \"2000-01-01\"\nlife_expectancy = 90\n").unwrap();
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--life-expectancy"]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_config(config)
+            .stack_args_layer(&args)
+            .unwrap();
+        assert_eq!(builder.life_expectancy, Some(90));
+
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--life-expectancy", "60"]);
+        let builder = builder.stack_args_layer(&args).unwrap();
+        assert_eq!(builder.life_expectancy, Some(60));
+
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&Args::parse_from([THIS_PROGRAM_NAME, "--life-expectancy"]))
+            .unwrap();
+        assert_eq!(builder.life_expectancy, Some(DEFAULT_LIFE_EXPECTANCY));
+    }
+
+    #[test]
+    fn birthday_window() {
+        let messages = |on, window| {
+            App {
+                birthday: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+                current_date: on,
+                wish_happy_birthday: true,
+                birthday_window: window,
+                ..Default::default()
+            }
+            .calculate()
+            .messages
+        };
+        assert_eq!(
+            messages(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 3),
+            ["Happy birthday!"]
+        );
+        assert_eq!(
+            messages(NaiveDate::from_ymd_opt(2023, 12, 30).unwrap(), 3),
+            ["Your birthday is in 2 days!"]
+        );
+        assert_eq!(
+            messages(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), 3),
+            ["Your birthday was 1 day ago!"]
+        );
+        assert!(messages(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap(), 3).is_empty());
+        assert!(messages(NaiveDate::from_ymd_opt(2023, 12, 30).unwrap(), 0).is_empty());
+    }
+
+    #[test]
+    fn week_start_changes_week_of_life() {
+        let week = |week_start| {
+            App {
+                // A Wednesday, and the Sunday after it
+                birthday: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+                current_date: NaiveDate::from_ymd_opt(2024, 1, 7).unwrap(),
+                week_start,
+                ..Default::default()
+            }
+            .week_of_life()
+        };
+        assert_eq!(week(None), 1);
+        assert_eq!(week(Some(WeekStart::Sunday)), 2);
+        assert_eq!(week(Some(WeekStart::Monday)), 1);
+    }
+
+    #[test]
+    fn heartbeats() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            birth_time: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            heartbeats: Some(60),
+            ..Default::default()
+        };
+        // 12 hours at 60 bpm
+        assert_eq!(app.heartbeats(60), Some(43_200));
+        assert_eq!(app.heartbeats(72), Some(51_840));
+        assert_eq!(
+            app.calculate().extras,
+            ["Your heart has beaten about 43200 times."]
+        );
+
+        let ancient = App {
+            birthday: NaiveDate::from_ymd_opt(-10000, 1, 1).unwrap(),
+            ..app
+        };
+        assert_eq!(ancient.heartbeats(u32::MAX), None);
+    }
+
+    #[test]
+    fn last_birthday() {
+        let app = |on: &str| App {
+            birthday: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            current_date: on.parse().unwrap(),
+            ..Default::default()
+        };
+        let june_15 = |year| NaiveDate::from_ymd_opt(year, 6, 15).unwrap();
+        assert_eq!(app("2024-06-01").last_birthday(), Some((33, june_15(2023))));
+        assert_eq!(app("2024-06-15").last_birthday(), Some((34, june_15(2024))));
+        assert_eq!(app("2024-12-31").last_birthday(), Some((34, june_15(2024))));
+        assert_eq!(
+            app("2024-06-01").last_birthday_string().as_deref(),
+            Some("33, on 2023-06-15")
+        );
+        assert_eq!(app("1990-06-15").last_birthday(), Some((0, june_15(1990))));
+        assert_eq!(app("1980-01-01").last_birthday(), None);
+
+        // Feb 29 birthdays are observed on Feb 28 in non-leap years
+        let leap = App {
+            birthday: NaiveDate::from_ymd_opt(2000, 2, 29).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            leap.last_birthday(),
+            Some((23, NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()))
+        );
+    }
+
+    #[test]
+    fn birthday_greeting() {
+        let app = |birthday: &str, on: &str| App {
+            birthday: birthday.parse().unwrap(),
+            current_date: on.parse().unwrap(),
+            full_birthday: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            app("2000-06-15", "2024-06-15")
+                .birthday_greeting()
+                .as_deref(),
+            Some("Happy birthday!")
+        );
+        assert_eq!(app("2000-06-15", "2024-06-14").birthday_greeting(), None);
+        assert!(app("2000-02-29", "2023-02-28")
+            .birthday_greeting()
+            .is_some());
+        let year_only = App {
+            full_birthday: false,
+            ..app("2000-01-01", "2024-01-01")
+        };
+        assert_eq!(year_only.birthday_greeting(), None);
+    }
+
+    #[test]
+    fn leap_day_age_agrees_with_extras_on_feb_28() {
+        let app = App {
+            birthday: NaiveDate::from_ymd_opt(2000, 2, 29).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2023, 2, 28).unwrap(),
+            wish_happy_birthday: true,
+            full_birthday: true,
+            verbosity: Verbosity::Normal,
+            countdown: true,
+            year_progress: true,
+            count_birthdays: true,
+            ..Default::default()
+        };
+        let report = app.calculate();
+        assert_eq!(report.age_string(), "23");
+        assert_eq!(report.messages, vec!["Happy birthday!"]);
+        assert_eq!(app.birthday_greeting().as_deref(), Some("Happy birthday!"));
+        assert_eq!(age(app.birthday, app.current_date), Some(23));
+        assert_eq!(
+            report.extras,
+            vec![
+                "Today is your birthday!",
+                "0% of the way to 24",
+                "You have had 23 birthdays.",
+            ]
+        );
+        assert_eq!(
+            app.last_birthday_string().as_deref(),
+            Some("23, on 2023-02-28")
+        );
+    }
+
+    #[test]
+    fn birthday_day_of_year() {
+        let day = |month, day, this_year| {
+            App {
+                birthday: NaiveDate::from_ymd_opt(2000, month, day).unwrap(),
+                current_date: NaiveDate::from_ymd_opt(this_year, 6, 1).unwrap(),
+                ..Default::default()
+            }
+            .birthday_day_of_year()
+        };
+        assert_eq!(day(1, 1, 2023), Some(1));
+        assert_eq!(day(1, 1, 2024), Some(1));
+        // Feb 29 pushes every later day back by one in leap years
+        assert_eq!(day(3, 1, 2023), Some(60));
+        assert_eq!(day(3, 1, 2024), Some(61));
+        assert_eq!(day(12, 31, 2024), Some(366));
+        // Observed on Feb 28 outside leap years
+        assert_eq!(day(2, 29, 2023), Some(59));
+        assert_eq!(day(2, 29, 2024), Some(60));
+    }
+
+    #[test]
+    fn turning_this_year() {
+        let turning = |month, day| {
+            App {
+                birthday: NaiveDate::from_ymd_opt(1990, month, day).unwrap(),
+                current_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+                ..Default::default()
+            }
+            .turning()
+        };
+        // Already passed this year
+        assert_eq!(turning(1, 15), 34);
+        // Still to come, so a year more than the current age
+        assert_eq!(turning(12, 25), 34);
+        assert_eq!(turning(6, 1), 34);
+    }
+
+    #[test]
+    fn week_of_life() {
+        let week = |days| {
+            let birthday = NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+            App {
+                birthday,
+                current_date: birthday + chrono::Duration::days(days),
+                ..Default::default()
+            }
+            .week_of_life()
+        };
+        assert_eq!(week(0), 1);
+        assert_eq!(week(6), 1);
+        assert_eq!(week(7), 2);
+    }
+
+    #[test]
+    fn relative_dates() {
+        let today = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let parse = |expr| parse_relative_date(expr, today);
+        assert_eq!(
+            parse("+30d").unwrap().unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 30).unwrap()
+        );
+        assert_eq!(
+            parse("-1y").unwrap().unwrap(),
+            NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()
+        );
+        assert_eq!(
+            parse("+2w").unwrap().unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 14).unwrap()
+        );
+        assert_eq!(
+            parse("-1m").unwrap().unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 29).unwrap()
+        );
+        assert!(parse("+30x").unwrap().is_err());
+        assert!(parse("+d").unwrap().is_err());
+        assert!(parse("2024-01-01").is_none());
+    }
+
+    #[test]
+    fn relative_date_arg() {
+        let instant = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "2000-06-15",
+            "--date",
+            "+30d",
+        ]);
+        let app = LayeredAppConfigBuilder::new()
+            .utc(true)
+            .clock(FixedClock(instant))
+            .stack_args_layer(&args)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            app.current_date,
+            NaiveDate::from_ymd_opt(2024, 7, 1).unwrap()
+        );
+        assert_eq!(app.calculate().age, 24);
+    }
+
+    #[test]
+    fn singular_counts() {
+        assert_eq!(plural(1, "year"), "1 year");
+        assert_eq!(plural(0, "year"), "0 years");
+        assert_eq!(plural(2u32, "day"), "2 days");
+        assert_eq!(plural(-1, "day"), "-1 day");
+
+        let birthday = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        let app = App {
+            birthday,
+            current_date: NaiveDate::from_ymd_opt(2024, 2, 2).unwrap(),
+            verbosity: Verbosity::Verbose,
+            count_birthdays: true,
+            ..Default::default()
+        };
+        let report = app.calculate();
+        assert_eq!(
+            report.messages,
+            vec!["You are 1 year old (born 2023-01-01, as of 2024-02-02)."]
+        );
+        assert_eq!(report.extras, vec!["You have had 1 birthday."]);
+        assert_eq!(
+            app.breakdown().unwrap().to_string(),
+            "1 year, 1 month, 1 day"
+        );
+
+        let future = App {
+            birthday: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            current_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            future.calculate().messages,
+            vec!["Born 1 year in the future"]
+        );
+        assert_eq!(
+            Elapsed::between(birthday, NaiveDate::from_ymd_opt(2023, 1, 2).unwrap())
+                .unwrap()
+                .to_string(),
+            "0 years (1 day)"
+        );
+    }
+
+    #[test]
+    fn verbosity_from_count() {
+        assert_eq!(Verbosity::from_count(0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_count(1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_count(2), Verbosity::Debug);
+        assert_eq!(Verbosity::from_count(5), Verbosity::Debug);
+
+        let verbosity = |flags: &[&str]| {
+            let args = Args::parse_from([THIS_PROGRAM_NAME].iter().chain(flags));
+            LayeredAppConfigBuilder::new()
+                .stack_args_layer(&args)
+                .unwrap()
+                .verbosity
+        };
+        assert_eq!(verbosity(&[]), Verbosity::Normal);
+        assert_eq!(verbosity(&["-v"]), Verbosity::Verbose);
+        assert_eq!(verbosity(&["-vv"]), Verbosity::Debug);
+        assert_eq!(verbosity(&["--verbose", "--verbose"]), Verbosity::Debug);
+    }
+
+    #[test]
+    fn timeline_of_dates() {
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--birthday",
+            "2000-06-15",
+            "--at",
+            "2010-01-01",
+            "--at",
+            "2020-06-15",
+            "--at",
+            "2005-07-01",
+        ]);
+        let apps = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .build_timeline(&args.at)
+            .unwrap();
+        let ages: Vec<_> = apps
+            .iter()
+            .map(|app| (app.current_date.to_string(), app.calculate().age))
+            .collect();
+        assert_eq!(
+            ages,
+            vec![
+                ("2010-01-01".to_owned(), 9),
+                ("2020-06-15".to_owned(), 20),
+                ("2005-07-01".to_owned(), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn detect_format() {
+        let detect = |sample| DateFormat::detect(sample).map(|format| format.as_fmt_string());
+        assert_eq!(detect("31/12/1998").as_deref(), Some("%d/%m/%Y"));
+        assert_eq!(detect("12/31/1998").as_deref(), Some("%m/%d/%Y"));
+        assert_eq!(detect("1998.01.05").as_deref(), Some("%Y.%m.%d"));
+        assert_eq!(detect("05/01/1998"), None);
+        assert_eq!(detect("not a date"), None);
+    }
+
+    #[test]
+    fn detect_format_falls_back() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "format = {{ DMY = {{ separator = \"-\" }} }}").unwrap();
+
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--format",
+            "detect",
+            "--birthday",
+            "05-01-1998",
+        ]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_file_layer(file.path())
+            .stack_args_layer(&args)
+            .unwrap();
+        assert_eq!(
+            builder.build().unwrap().birthday,
+            NaiveDate::from_ymd_opt(1998, 1, 5).unwrap()
+        );
+        assert_eq!(builder.provenance.format, Layer::File);
+
+        let args = Args::parse_from([
+            THIS_PROGRAM_NAME,
+            "--format",
+            "detect",
+            "--birthday",
+            "12/31/1998",
+        ]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_file_layer(file.path())
+            .stack_args_layer(&args)
+            .unwrap();
+        assert_eq!(
+            builder.build().unwrap().birthday,
+            NaiveDate::from_ymd_opt(1998, 12, 31).unwrap()
+        );
+    }
+
+    #[test]
+    fn detect_format_from_stdin() {
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--format", "detect"]);
+        let builder = LayeredAppConfigBuilder::new()
+            .stack_args_layer(&args)
+            .unwrap()
+            .stack_reader_layer("31/12/1998\n".as_bytes())
+            .unwrap();
+        assert_eq!(
+            builder.build().unwrap().birthday,
+            NaiveDate::from_ymd_opt(1998, 12, 31).unwrap()
+        );
+        assert_eq!(builder.explain()[1], "format: DMY/ (from the command line)");
+    }
+
+    #[test]
+    fn no_config_conflicts_with_config() {
+        let args =
+            Args::try_parse_from([THIS_PROGRAM_NAME, "--no-config", "--config", "config.toml"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn porcelain_and_compact_conflict_with_since() {
+        for flag in ["--porcelain", "--compact"] {
+            let args = Args::try_parse_from([THIS_PROGRAM_NAME, "--since", "1990-01-01", flag]);
+            assert!(args.is_err());
+        }
+    }
+
+    #[test]
+    fn porcelain_and_compact_conflict_with_other_birthday() {
+        for flag in ["--porcelain", "--compact"] {
+            let args = Args::try_parse_from([
+                THIS_PROGRAM_NAME,
+                "--birthday",
+                "2000-01-01",
+                "--other-birthday",
+                "1990-01-01",
+                flag,
+            ]);
+            assert!(args.is_err());
+        }
+    }
+
+    #[test]
+    fn porcelain_and_compact_conflict_with_birthdays_between() {
+        for flag in ["--porcelain", "--compact"] {
+            let args = Args::try_parse_from([
+                THIS_PROGRAM_NAME,
+                "--birthday",
+                "2000-01-01",
+                "--birthdays-between",
+                "2019-01-01",
+                "2021-12-31",
+                flag,
+            ]);
+            assert!(args.is_err());
+        }
+    }
+
+    #[test]
+    fn at_accepts_negative_offsets() {
+        let args = Args::parse_from([THIS_PROGRAM_NAME, "--at", "-1y"]);
+        assert_eq!(args.at, vec!["-1y"]);
+    }
+
+    #[test]
+    fn precision_is_capped() {
+        let precision = |value| Args::try_parse_from([THIS_PROGRAM_NAME, "--precision", value]);
+        assert_eq!(precision("15").unwrap().precision, 15);
+        assert!(precision("16").is_err());
+        assert!(precision("400").is_err());
+    }
+}
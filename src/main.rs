@@ -1,423 +1,500 @@
-use chrono::Datelike;
-use chrono::{Local, NaiveDate};
 use clap::Parser;
-use color_eyre::eyre::{bail, Result};
-use directories::ProjectDirs;
-use serde::Deserialize;
-use std::fmt;
-use std::path::Path;
-use std::str;
-
-const THIS_PROGRAM_NAME: &str = env!("CARGO_PKG_NAME");
-
-#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone)]
-enum Verbosity {
-    Quiet = 1,
-    Normal = 2,
-    Verbose = 3,
+use color_eyre::eyre::{bail, Result, WrapErr};
+use howoldami::{
+    error_json, exit_code, format_examples, style_age, within_bounds, write_completions, App, Args,
+    ColorChoice, Command, ErrorFormat, LayeredAppConfigBuilder, OutputFormat, PersonReport, Report,
+    Verbosity, CONFIG_TEMPLATE, EXIT_FAILURE, EXIT_NOT_BIRTHDAY,
+};
+use std::io::{IsTerminal, Write};
+
+/// Print a report in plain mode, with `result` standing in for the age line
+fn print_plain(out: &mut dyn Write, report: &Report, result: &str, color: bool) -> Result<()> {
+    for message in &report.messages {
+        writeln!(out, "{}", message)?;
+    }
+    writeln!(out, "{}", style_age(result, color))?;
+    for extra in &report.extras {
+        writeln!(out, "{}", extra)?;
+    }
+    Ok(())
 }
 
-#[derive(Debug)]
-enum DateSpecifier {
-    Full(String),
-    YearOnly(String),
+/// Whether `--at-least` or `--at-most` turn the run into a silent age check
+fn is_bounded(args: &Args) -> bool {
+    args.at_least.is_some() || args.at_most.is_some()
 }
 
-impl DateSpecifier {
-    fn year(year: &str) -> Self {
-        Self::YearOnly(year.to_owned())
+/// For a bounded run, exit with [`EXIT_FAILURE`] unless every age is within the bounds
+fn exit_unless_within_bounds(ages: impl IntoIterator<Item = i64>, args: &Args) {
+    if !ages
+        .into_iter()
+        .all(|age| within_bounds(age, args.at_least, args.at_most))
+    {
+        std::process::exit(EXIT_FAILURE);
     }
+}
 
-    fn full(date: &str) -> Self {
-        Self::Full(date.to_owned())
+/// The age line for a report, picked by the flags that replace the bare age
+fn age_result(app: &App, report: &Report, args: &Args) -> Result<String> {
+    if args.format_output == OutputFormat::IsoDuration {
+        return Ok(report.iso_duration(args.duration_zeros));
     }
-
-    fn to_naive_date(&self, format_str: &str) -> Result<NaiveDate> {
-        let date = match self {
-            DateSpecifier::Full(date) => NaiveDate::parse_from_str(&date, &format_str)?,
-            DateSpecifier::YearOnly(year) => {
-                let year = year.parse::<i32>()?;
-                // Default to January 1st for evaluation purposes
-                NaiveDate::from_yo_opt(year, 1)
-                    .ok_or_else(|| format!("Invalid year: {}", year))
-                    .unwrap()
-            }
+    let result = if let Some(template) = &args.template {
+        app.render_template(template)?
+    } else if args.precise {
+        let Some(breakdown) = app.breakdown() else {
+            bail!("Cannot break down an age when the birthday is in the future");
         };
-        Ok(date)
-    }
-
-    fn is_full(&self) -> bool {
-        match self {
-            DateSpecifier::Full(_) => true,
-            DateSpecifier::YearOnly(_) => false,
-        }
-    }
+        breakdown.to_string()
+    } else if args.days_alive {
+        app.days_alive().to_string()
+    } else if args.as_of_last_birthday {
+        let Some(last) = app.last_birthday_string() else {
+            bail!("You haven't had a birthday yet");
+        };
+        last
+    } else if args.day_of_year {
+        let Some(day) = app.birthday_day_of_year() else {
+            bail!("Could not determine this year's birthday");
+        };
+        day.to_string()
+    } else if args.turning {
+        app.turning().to_string()
+    } else if args.week_number {
+        app.week_of_life().to_string()
+    } else if args.decimal {
+        args.rounding
+            .format(app.decimal_age(), args.precision.into())
+    } else {
+        report.age_string()
+    };
+    Ok(result)
 }
 
-#[derive(Debug, Deserialize)]
-enum DateFormat {
-    /// Month, day, year
-    MDY { separator: char },
-    /// Day, month, year
-    DMY { separator: char },
-    /// Year, month, day
-    YMD { separator: char },
+/// Whether the subcommand answers for one birthday on one date, rather than the age
+fn is_single_date_command(args: &Args) -> bool {
+    matches!(
+        args.command,
+        Some(Command::Countdown | Command::Zodiac { .. })
+    )
 }
 
-impl Default for DateFormat {
-    fn default() -> Self {
-        DateFormat::MDY { separator: '/' }
+/// Send a report's messages to stderr so stdout stays parseable
+fn print_messages_to_stderr(report: &Report) {
+    for message in report.messages.iter().chain(&report.extras) {
+        eprintln!("{}", message);
     }
 }
 
-impl fmt::Display for DateFormat {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            DateFormat::MDY { separator } => write!(
-                f,
-                "%m{}%d{}%Y",
-                separator.to_string(),
-                separator.to_string()
-            ),
-            DateFormat::DMY { separator } => write!(
-                f,
-                "%d{}%m{}%Y",
-                separator.to_string(),
-                separator.to_string()
-            ),
-            DateFormat::YMD { separator } => write!(
-                f,
-                "%Y{}%m{}%d",
-                separator.to_string(),
-                separator.to_string()
-            ),
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let args = Args::parse();
+    let error_format = args.error_format;
+    if let Err(err) = run(args) {
+        match error_format {
+            ErrorFormat::Human => eprintln!("Error: {:?}", err),
+            ErrorFormat::Json => eprintln!("{}", error_json(&err)),
         }
+        std::process::exit(exit_code(&err));
     }
+    Ok(())
 }
 
-impl str::FromStr for DateFormat {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.chars();
-        let first = chars.next().ok_or("No first character found")?;
-        let second = chars.next().ok_or("No second character found")?;
-        let third = chars.next().ok_or("No third character found")?;
-        let separator = chars.next().ok_or("No separator found")?;
-
-        let format = match (first, second, third) {
-            ('M', 'D', 'Y') => DateFormat::MDY { separator },
-            ('D', 'M', 'Y') => DateFormat::DMY { separator },
-            ('Y', 'M', 'D') => DateFormat::YMD { separator },
-            _ => return Err("Invalid date format".to_owned()),
-        };
-        Ok(format)
+fn run(args: Args) -> Result<()> {
+    if let Some(Command::Completions { shell }) = args.command {
+        write_completions(shell, &mut std::io::stdout());
+        return Ok(());
     }
-}
 
-impl DateFormat {
-    fn as_fmt_string(&self) -> String {
-        match self {
-            DateFormat::MDY { separator } => {
-                format!("%m{}%d{}%Y", separator.to_string(), separator.to_string())
-            }
-            DateFormat::DMY { separator } => {
-                format!("%d{}%m{}%Y", separator.to_string(), separator.to_string())
-            }
-            DateFormat::YMD { separator } => {
-                format!("%Y{}%m{}%d", separator.to_string(), separator.to_string())
-            }
-        }
+    if is_single_date_command(&args)
+        && (args.porcelain || args.compact || args.format_output != OutputFormat::Plain)
+    {
+        bail!("The countdown and zodiac subcommands only print plain text");
     }
-}
 
-#[derive(Debug, Deserialize)]
-struct ConfigFile {
-    birthday: Option<String>,
-    birthyear: Option<String>,
-    format: Option<DateFormat>,
-}
-
-impl ConfigFile {
-    fn from_file(path: &Path) -> Result<Self> {
-        let contents = ::std::fs::read_to_string(path)?;
-        let config: ConfigFile = toml::from_str(&contents)?;
-        Ok(config)
+    if args.config_schema {
+        print!("{}", CONFIG_TEMPLATE);
+        return Ok(());
     }
-}
 
-#[derive(Debug)]
-struct App {
-    birthday: NaiveDate,
-    current_date: NaiveDate,
-    verbosity: Verbosity,
-    wish_happy_birthday: bool,
-}
+    if args.list_formats {
+        for line in format_examples() {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
 
-impl App {
-    fn calculate(&self) -> u32 {
-        let current_date = self.current_date;
-        let birthday = self.birthday;
+    let verbosity = if args.porcelain || args.compact || args.quiet {
+        Verbosity::Quiet
+    } else {
+        Verbosity::from_count(args.verbose)
+    };
 
-        if self.verbosity == Verbosity::Verbose {
-            println!("Current date: {:?}", current_date);
-            println!("Birthday: {:?}", birthday);
-        }
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(
+            std::fs::File::create(path)
+                .wrap_err_with(|| format!("Could not create output file {}", path.display()))?,
+        ),
+        None => Box::new(std::io::stdout()),
+    };
 
-        if self.wish_happy_birthday
-            && self.verbosity >= Verbosity::Normal
-            && current_date.month() == birthday.month()
-            && current_date.day() == birthday.day()
-        {
-            println!("Happy birthday!");
-        }
+    // Auto color follows stdout, which a file given with --output replaces
+    let color = args.format_output == OutputFormat::Plain
+        && match (args.color, &args.output) {
+            (ColorChoice::Auto, Some(_)) => false,
+            (choice, _) => choice.enabled(),
+        };
 
-        let age = current_date.years_since(birthday).unwrap();
-        age
+    let mut config_builder = LayeredAppConfigBuilder::new()
+        .verbosity(verbosity)
+        .countdown(args.countdown)
+        .weekday(args.weekday)
+        .unit(args.unit)
+        .zodiac(args.zodiac)
+        .century_pivot(args.century_pivot)
+        .allow_future(args.allow_future)
+        .sleeps(args.sleeps)
+        .utc(args.utc)
+        .half_birthday(args.half_birthday)
+        .born_on_leap_year(args.born_on_leap_year)
+        .milestones(
+            (args.milestones || !args.milestone_ages.is_empty())
+                .then(|| args.milestone_ages.clone()),
+        )
+        .year_progress(args.year_progress)
+        .color(color)
+        .timezone(args.timezone)
+        .next_milestone(args.next_milestone.then_some(args.milestone_step))
+        .dog_years(args.dog_years.then_some(args.dog_year_formula))
+        .count_birthdays(args.count_birthdays)
+        .birthday_window(args.birthday_window)
+        .calendar(args.calendar)
+        .other_birthday(args.other_birthday.clone())
+        .birthdays_between(args.birthdays_between.clone())
+        .profile(args.profile.clone())
+        .retire_at(args.retire_at)
+        .week_start(args.week_start)
+        .historical_year_math(args.historical_year_math)
+        .heartbeats(args.heartbeats.then_some(args.bpm))
+        .strict(args.strict)
+        .strict_config(args.strict_config)
+        .clamp_years(args.clamp_years)
+        .stack_config_layer(&args)?
+        .stack_env_layer()?
+        .stack_args_layer(&args)?;
+    if args.stdin {
+        config_builder = config_builder.stack_reader_layer(std::io::stdin().lock())?;
+    }
+    // Without a terminal to answer it, the prompt is skipped and a missing birthday errors
+    if args.interactive && std::io::stdin().is_terminal() {
+        config_builder =
+            config_builder.stack_prompt_layer(std::io::stdin().lock(), &mut std::io::stderr())?;
     }
-}
-
-#[derive(Debug)]
-struct LayeredAppConfigBuilder {
-    birthday: Option<DateSpecifier>,
-    current_date: Option<DateSpecifier>,
-    format: DateFormat,
-    verbosity: Verbosity,
-}
 
-impl LayeredAppConfigBuilder {
-    fn new() -> Self {
-        Self {
-            birthday: None,
-            current_date: None,
-            format: DateFormat::default(),
-            verbosity: Verbosity::Normal,
+    if args.explain {
+        for line in config_builder.explain() {
+            writeln!(out, "{}", line)?;
         }
+        return Ok(());
     }
 
-    fn verbosity(mut self, verbosity: Verbosity) -> Self {
-        self.verbosity = verbosity;
-        self
+    if let Some(path) = &args.batch {
+        if is_single_date_command(&args) {
+            bail!("The countdown and zodiac subcommands take a single birthday, not --batch");
+        }
+        let file = std::fs::File::open(path)
+            .wrap_err_with(|| format!("Could not open batch file {}", path.display()))?;
+        let mut reports = Vec::new();
+        let mut ages = Vec::new();
+        for line in config_builder.build_batch(std::io::BufReader::new(file))? {
+            if let Some(error) = line
+                .error_json()
+                .filter(|_| args.format_output == OutputFormat::Jsonl)
+            {
+                writeln!(out, "{}", error)?;
+                continue;
+            }
+            let app = match line.app {
+                Ok(app) => app,
+                Err(err) => {
+                    eprintln!("Line {}: '{}': {}", line.line_number, line.input, err);
+                    continue;
+                }
+            };
+            let report = app.calculate();
+            if args.porcelain {
+                writeln!(out, "{}", report.porcelain())?;
+                continue;
+            }
+            if is_bounded(&args) {
+                ages.push(report.age);
+                continue;
+            }
+            match args.format_output {
+                OutputFormat::Plain | OutputFormat::IsoDuration => {
+                    let result = match age_result(&app, &report, &args) {
+                        Ok(result) => result,
+                        Err(err) => {
+                            eprintln!("Line {}: '{}': {}", line.line_number, line.input, err);
+                            continue;
+                        }
+                    };
+                    print_plain(&mut out, &report, &result, color)?
+                }
+                OutputFormat::Json => {
+                    print_messages_to_stderr(&report);
+                    reports.push(report);
+                }
+                OutputFormat::Jsonl => {
+                    print_messages_to_stderr(&report);
+                    writeln!(out, "{}", serde_json::to_string(&report)?)?;
+                }
+            }
+        }
+        // Like a list of dates, every birthday has to be in bounds
+        if is_bounded(&args) {
+            exit_unless_within_bounds(ages, &args);
+            return Ok(());
+        }
+        if args.format_output == OutputFormat::Json {
+            writeln!(out, "{}", serde_json::to_string(&reports)?)?;
+        }
+        return Ok(());
     }
 
-    fn stack_args_layer(mut self, args: &Args) -> Self {
-        if let Some(birthday) = &args.birthday {
-            self.birthday = Some(DateSpecifier::full(birthday));
-        } else if let Some(birthyear) = &args.birthyear {
-            self.birthday = Some(DateSpecifier::year(birthyear));
+    if config_builder.has_people() {
+        if is_single_date_command(&args) {
+            bail!("The countdown and zodiac subcommands take a single birthday, not [[person]] entries");
         }
-
-        if let Some(date) = &args.date {
-            self.current_date = Some(DateSpecifier::full(date));
-        } else if let Some(year) = &args.year {
-            self.current_date = Some(DateSpecifier::year(year));
+        let apps = config_builder.build_people()?;
+        if args.show_parsed {
+            for (name, app) in &apps {
+                for line in app.parsed_dates() {
+                    eprintln!("{}: {}", name, line);
+                }
+            }
         }
-
-        if let Some(format) = &args.format {
-            self.format = format.parse().unwrap();
+        if args.notify_on_birthday {
+            let greetings: Vec<_> = apps
+                .iter()
+                .filter_map(|(name, app)| Some((name, app.birthday_greeting()?)))
+                .collect();
+            if greetings.is_empty() {
+                std::process::exit(EXIT_NOT_BIRTHDAY);
+            }
+            for (name, greeting) in greetings {
+                writeln!(out, "{}: {}", name, greeting)?;
+            }
+            return Ok(());
+        }
+        let people: Vec<_> = apps
+            .iter()
+            .map(|(name, app)| PersonReport {
+                name: name.clone(),
+                report: app.calculate(),
+            })
+            .collect();
+        if args.porcelain {
+            for person in &people {
+                writeln!(out, "{}\t{}", person.name, person.report.porcelain())?;
+            }
+            return Ok(());
+        }
+        // In the order of the config file, like the ages of a list of dates
+        if args.compact {
+            let ages: Vec<_> = people
+                .iter()
+                .map(|person| person.report.age_string())
+                .collect();
+            write!(out, "{}", ages.join("\n"))?;
+            return Ok(());
+        }
+        if is_bounded(&args) {
+            exit_unless_within_bounds(people.iter().map(|person| person.report.age), &args);
+            return Ok(());
+        }
+        match args.format_output {
+            OutputFormat::Plain | OutputFormat::IsoDuration => {
+                for ((_, app), person) in apps.iter().zip(&people) {
+                    let result = age_result(app, &person.report, &args)?;
+                    let result = format!("{}: {}", person.name, result);
+                    print_plain(&mut out, &person.report, &result, color)?;
+                }
+            }
+            OutputFormat::Json => {
+                for person in &people {
+                    print_messages_to_stderr(&person.report);
+                }
+                writeln!(out, "{}", serde_json::to_string(&people)?)?;
+            }
+            OutputFormat::Jsonl => {
+                for person in &people {
+                    print_messages_to_stderr(&person.report);
+                    writeln!(out, "{}", serde_json::to_string(person)?)?;
+                }
+            }
         }
-        self
+        return Ok(());
     }
 
-    fn stack_file_layer(mut self, path: &Path) -> Self {
-        let Ok(config) = ConfigFile::from_file(path) else {
-            if self.verbosity == Verbosity::Verbose {
-                eprintln!("Could not read config file");
+    if args.at.len() > 1 {
+        if args.since.is_some()
+            || args.other_birthday.is_some()
+            || !args.birthdays_between.is_empty()
+            || is_single_date_command(&args)
+        {
+            bail!(
+                "--since, --other-birthday, --birthdays-between, and the countdown and zodiac \
+                 subcommands take a single --at date"
+            );
+        }
+        let apps = config_builder.build_timeline(&args.at)?;
+        let reports: Vec<_> = apps.iter().map(|app| app.calculate()).collect();
+        if args.notify_on_birthday {
+            let greetings: Vec<_> = apps
+                .iter()
+                .zip(&reports)
+                .filter_map(|(app, report)| Some((report.current_date, app.birthday_greeting()?)))
+                .collect();
+            if greetings.is_empty() {
+                std::process::exit(EXIT_NOT_BIRTHDAY);
             }
-            return self;
-        };
-
-        // Redundant if both are set - birthday takes precedence
-        if let Some(birthday) = config.birthday {
-            self.birthday = Some(DateSpecifier::full(&birthday));
-        } else if let Some(birthyear) = config.birthyear {
-            self.birthday = Some(DateSpecifier::year(&birthyear));
+            for (date, greeting) in greetings {
+                writeln!(out, "{}: {}", date, greeting)?;
+            }
+            return Ok(());
         }
-
-        if let Some(format) = config.format {
-            self.format = format;
+        if args.porcelain {
+            for report in &reports {
+                writeln!(out, "{}", report.porcelain())?;
+            }
+            return Ok(());
         }
-        self
+        if args.compact {
+            let ages: Vec<_> = reports.iter().map(Report::age_string).collect();
+            write!(out, "{}", ages.join("\n"))?;
+            return Ok(());
+        }
+        // Every date has to be in bounds, so one out of range fails the whole run
+        if is_bounded(&args) {
+            exit_unless_within_bounds(reports.iter().map(|report| report.age), &args);
+            return Ok(());
+        }
+        match args.format_output {
+            OutputFormat::Plain | OutputFormat::IsoDuration => {
+                for (app, report) in apps.iter().zip(&reports) {
+                    let result = age_result(app, report, &args)?;
+                    let result = format!("{}: {}", report.current_date, result);
+                    print_plain(&mut out, report, &result, color)?;
+                }
+            }
+            OutputFormat::Json => {
+                for report in &reports {
+                    print_messages_to_stderr(report);
+                }
+                writeln!(out, "{}", serde_json::to_string(&reports)?)?;
+            }
+            OutputFormat::Jsonl => {
+                for report in &reports {
+                    print_messages_to_stderr(report);
+                    writeln!(out, "{}", serde_json::to_string(report)?)?;
+                }
+            }
+        }
+        return Ok(());
     }
 
-    fn build(&self) -> Result<App> {
-        let format = self.format.as_fmt_string();
-
-        let Some(birthday) = &self.birthday else {
-            bail!("No birthday specified in either config or command line args");
+    let app = config_builder.build()?;
+    if args.show_parsed {
+        for line in app.parsed_dates() {
+            eprintln!("{}", line);
+        }
+    }
+    if args.since.is_some() {
+        let Some(elapsed) = app.elapsed() else {
+            bail!("The --since date is after the current date");
         };
+        match args.format_output {
+            OutputFormat::Plain | OutputFormat::IsoDuration => writeln!(out, "{}", elapsed)?,
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                writeln!(out, "{}", serde_json::to_string(&elapsed)?)?
+            }
+        }
+        return Ok(());
+    }
 
-        let mut wish_happy_birthday = birthday.is_full();
-        let birthday = birthday.to_naive_date(&format)?;
-
-        let current_date = if let Some(current_date) = &self.current_date {
-            current_date.to_naive_date(&format)?
-        } else {
-            wish_happy_birthday = false;
-            let current_date = Local::now().naive_local().date();
-            current_date
+    if args.notify_on_birthday {
+        let Some(greeting) = app.birthday_greeting() else {
+            std::process::exit(EXIT_NOT_BIRTHDAY);
         };
-
-        let verbosity = self.verbosity;
-
-        Ok(App {
-            birthday,
-            current_date,
-            verbosity,
-            wish_happy_birthday,
-        })
+        writeln!(out, "{}", greeting)?;
+        return Ok(());
     }
-}
-
-#[derive(Debug, Parser)]
-struct Args {
-    /// Increase message verbosity
-    #[clap(short, long, group = "verbosity")]
-    verbose: bool,
-
-    /// Silence all output except the user's age
-    #[clap(short, long, group = "verbosity")]
-    quiet: bool,
-
-    /// Override today's date
-    #[clap(short, long, group = "current_date")]
-    date: Option<String>,
-
-    /// Override today's date, but just the year
-    #[clap(short, long, group = "current_date")]
-    year: Option<String>,
-
-    /// Specify your birthday
-    #[clap(short, long, group = "birthday_specifier")]
-    birthday: Option<String>,
-
-    /// Specify just your birth year
-    #[clap(long, group = "birthday_specifier")]
-    birthyear: Option<String>,
-
-    /// Datetime format
-    #[clap(short, long)]
-    format: Option<String>,
-}
-
-fn main() -> Result<()> {
-    color_eyre::install()?;
-    let args = Args::parse();
-
-    let verbosity = if args.verbose {
-        Verbosity::Verbose
-    } else if args.quiet {
-        Verbosity::Quiet
-    } else {
-        Verbosity::Normal
-    };
 
-    let mut config_builder = LayeredAppConfigBuilder::new().verbosity(verbosity);
-    if let Some(proj_dirs) = ProjectDirs::from("", "", THIS_PROGRAM_NAME) {
-        let config_dir = proj_dirs.config_dir();
-        let config_file = config_dir.join("config.toml");
-        config_builder = config_builder.stack_file_layer(&config_file);
+    if let Some(gap) = app.age_gap() {
+        match args.format_output {
+            OutputFormat::Plain | OutputFormat::IsoDuration => writeln!(out, "{}", gap)?,
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                writeln!(out, "{}", serde_json::to_string(&gap)?)?
+            }
+        }
+        return Ok(());
     }
-    config_builder = config_builder.stack_args_layer(&args);
-
-    let app = config_builder.build()?;
-    let age = app.calculate();
-    println!("{}", age);
-    Ok(())
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use pretty_assertions::assert_eq;
-
-    #[test]
-    fn on_actual_birthday() {
-        let birthyear = 1998;
-        let diff = 0;
-        let currentyear = birthyear + diff;
-        let app = App {
-            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
-            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
-            wish_happy_birthday: false,
-            verbosity: Verbosity::Normal,
-        };
-        let age = app.calculate();
-        assert_eq!(age, diff as u32);
+    if let Some(anniversaries) = app.birthdays_between() {
+        match args.format_output {
+            OutputFormat::Plain | OutputFormat::IsoDuration => {
+                for anniversary in &anniversaries {
+                    writeln!(out, "{}", app.anniversary_message(anniversary))?;
+                }
+            }
+            OutputFormat::Json => writeln!(out, "{}", serde_json::to_string(&anniversaries)?)?,
+            OutputFormat::Jsonl => {
+                for anniversary in &anniversaries {
+                    writeln!(out, "{}", serde_json::to_string(anniversary)?)?;
+                }
+            }
+        }
+        return Ok(());
     }
 
-    #[test]
-    fn on_birthday() {
-        let birthyear = 1998;
-        let diff = 26;
-        let currentyear = birthyear + diff;
-        let app = App {
-            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
-            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
-            wish_happy_birthday: false,
-            verbosity: Verbosity::Normal,
-        };
-        let age = app.calculate();
-        assert_eq!(age, diff as u32);
+    match args.command {
+        Some(Command::Countdown) => {
+            writeln!(out, "{}", app.countdown_message())?;
+            return Ok(());
+        }
+        Some(Command::Zodiac { system }) => {
+            for line in app.zodiac_messages(system) {
+                writeln!(out, "{}", line)?;
+            }
+            return Ok(());
+        }
+        None | Some(Command::Age) | Some(Command::Completions { .. }) => {}
     }
 
-    #[test]
-    fn on_birthday_really_young() {
-        let birthyear = 1998;
-        let diff = 1;
-        let currentyear = birthyear + diff;
-        let app = App {
-            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
-            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
-            wish_happy_birthday: false,
-            verbosity: Verbosity::Normal,
-        };
-        let age = app.calculate();
-        assert_eq!(age, diff as u32);
+    let report = app.calculate();
+    if args.porcelain {
+        writeln!(out, "{}", report.porcelain())?;
+        return Ok(());
     }
-
-    #[test]
-    fn on_birthday_really_old() {
-        let birthyear = 1998;
-        let diff = 1000;
-        let currentyear = birthyear + diff;
-        let app = App {
-            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
-            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
-            wish_happy_birthday: false,
-            verbosity: Verbosity::Normal,
-        };
-        let age = app.calculate();
-        assert_eq!(age, diff as u32);
+    if args.compact {
+        write!(out, "{}", report.age_string())?;
+        return Ok(());
     }
-
-    #[test]
-    fn day_before_birthday() {
-        let birthyear = 1998;
-        let diff = 26;
-        let currentyear = birthyear + diff;
-        let app = App {
-            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 2).unwrap(),
-            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 1).unwrap(),
-            wish_happy_birthday: false,
-            verbosity: Verbosity::Normal,
-        };
-        let age = app.calculate();
-        assert_eq!(age, diff as u32 - 1);
+    if is_bounded(&args) {
+        exit_unless_within_bounds([report.age], &args);
+        return Ok(());
     }
 
-    #[test]
-    fn day_after_birthday() {
-        let birthyear = 1998;
-        let diff = 26;
-        let currentyear = birthyear + diff;
-        let app = App {
-            birthday: NaiveDate::from_ymd_opt(birthyear, 1, 1).unwrap(),
-            current_date: NaiveDate::from_ymd_opt(currentyear, 1, 2).unwrap(),
-            wish_happy_birthday: false,
-            verbosity: Verbosity::Normal,
-        };
-        let age = app.calculate();
-        assert_eq!(age, diff as u32);
+    match args.format_output {
+        OutputFormat::Plain | OutputFormat::IsoDuration => {
+            let result = age_result(&app, &report, &args)?;
+            print_plain(&mut out, &report, &result, color)?;
+        }
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            print_messages_to_stderr(&report);
+            writeln!(out, "{}", serde_json::to_string(&report)?)?;
+        }
     }
+    Ok(())
 }
@@ -0,0 +1,114 @@
+//! Zodiac signs for a birthday
+
+use chrono::{Datelike, NaiveDate};
+use clap::ValueEnum;
+
+/// Which zodiac to report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Zodiac {
+    Chinese,
+    Western,
+}
+
+const CHINESE_ANIMALS: [&str; 12] = [
+    "Rat", "Ox", "Tiger", "Rabbit", "Dragon", "Snake", "Horse", "Goat", "Monkey", "Rooster", "Dog",
+    "Pig",
+];
+
+/// The Chinese zodiac animal for a Gregorian year. 4 CE was a year of the Rat
+pub fn chinese_animal(year: i32) -> &'static str {
+    CHINESE_ANIMALS[(year - 4).rem_euclid(12) as usize]
+}
+
+/// Chinese New Year falls somewhere between Jan 21 and Feb 20, so a birthday before
+/// then belongs to the previous year's animal. We don't have a lunar calendar, so all
+/// we can do is flag the dates that might be affected
+pub fn may_precede_chinese_new_year(birthday: NaiveDate) -> bool {
+    birthday.month() == 1 || (birthday.month() == 2 && birthday.day() <= 20)
+}
+
+/// The first day of the sign that begins in each month, January first
+const WESTERN_SIGN_STARTS: [(u32, &str); 12] = [
+    (20, "Aquarius"),
+    (19, "Pisces"),
+    (21, "Aries"),
+    (20, "Taurus"),
+    (21, "Gemini"),
+    (21, "Cancer"),
+    (23, "Leo"),
+    (23, "Virgo"),
+    (23, "Libra"),
+    (23, "Scorpio"),
+    (22, "Sagittarius"),
+    (22, "Capricorn"),
+];
+
+/// The astrological sign for a birthday's month and day
+pub fn western_sign(birthday: NaiveDate) -> &'static str {
+    let month = birthday.month0() as usize;
+    let (start, sign) = WESTERN_SIGN_STARTS[month];
+    if birthday.day() >= start {
+        sign
+    } else {
+        // Still in the sign that began last month. Early January wraps back to Capricorn
+        WESTERN_SIGN_STARTS[(month + 11) % 12].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn known_years() {
+        assert_eq!(chinese_animal(1998), "Tiger");
+        assert_eq!(chinese_animal(2000), "Dragon");
+        assert_eq!(chinese_animal(2024), "Dragon");
+        assert_eq!(chinese_animal(1990), "Horse");
+        assert_eq!(chinese_animal(2019), "Pig");
+    }
+
+    #[test]
+    fn years_before_common_era() {
+        assert_eq!(chinese_animal(4), "Rat");
+        assert_eq!(chinese_animal(3), "Pig");
+        assert_eq!(chinese_animal(-8), "Rat");
+    }
+
+    #[test]
+    fn new_year_window() {
+        let date = |month, day| NaiveDate::from_ymd_opt(1998, month, day).unwrap();
+        assert!(may_precede_chinese_new_year(date(1, 28)));
+        assert!(may_precede_chinese_new_year(date(2, 20)));
+        assert!(!may_precede_chinese_new_year(date(2, 21)));
+        assert!(!may_precede_chinese_new_year(date(6, 1)));
+    }
+
+    #[test]
+    fn western_signs() {
+        let date = |month, day| NaiveDate::from_ymd_opt(1998, month, day).unwrap();
+        assert_eq!(western_sign(date(3, 25)), "Aries");
+        assert_eq!(western_sign(date(7, 4)), "Cancer");
+        assert_eq!(western_sign(date(10, 31)), "Scorpio");
+    }
+
+    #[test]
+    fn western_cusps() {
+        let date = |month, day| NaiveDate::from_ymd_opt(1998, month, day).unwrap();
+        assert_eq!(western_sign(date(3, 20)), "Pisces");
+        assert_eq!(western_sign(date(3, 21)), "Aries");
+        assert_eq!(western_sign(date(4, 19)), "Aries");
+        assert_eq!(western_sign(date(4, 20)), "Taurus");
+    }
+
+    #[test]
+    fn capricorn_wraps_the_new_year() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        assert_eq!(western_sign(date(1998, 12, 21)), "Sagittarius");
+        assert_eq!(western_sign(date(1998, 12, 22)), "Capricorn");
+        assert_eq!(western_sign(date(1999, 1, 1)), "Capricorn");
+        assert_eq!(western_sign(date(1999, 1, 19)), "Capricorn");
+        assert_eq!(western_sign(date(1999, 1, 20)), "Aquarius");
+    }
+}
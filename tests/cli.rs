@@ -0,0 +1,640 @@
+use std::io::Write;
+use std::process::Command;
+
+fn howoldami() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_howoldami"));
+    // A home without a ~/.birthday or config files
+    command
+        .env("HOME", concat!(env!("CARGO_TARGET_TMPDIR"), "/no-home"))
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("HOWOLDAMI_BIRTHDAY")
+        .env_remove("HOWOLDAMI_FORMAT");
+    command
+}
+
+#[test]
+fn missing_birthday_exits_with_4() {
+    let config = tempfile::NamedTempFile::new().unwrap();
+    let output = howoldami()
+        .arg("--config")
+        .arg(config.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn usage_errors_exit_apart_from_missing_birthday() {
+    let usage = howoldami()
+        .args(["--timezone", "Foo/Bar"])
+        .output()
+        .unwrap();
+    let missing = howoldami().arg("--no-config").output().unwrap();
+    assert_eq!(usage.status.code(), Some(2));
+    assert_ne!(usage.status.code(), missing.status.code());
+}
+
+#[test]
+fn other_errors_exit_with_1() {
+    let output = howoldami()
+        .args(["--birthday", "not a date", "--format", "XYZ/"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+fn bounded(bound: &str, age: &str) -> Option<i32> {
+    howoldami()
+        .args(["--birthday", "2000-01-01", "--at", "2024-06-01", bound, age])
+        .output()
+        .unwrap()
+        .status
+        .code()
+}
+
+#[test]
+fn at_least_sets_exit_code() {
+    assert_eq!(bounded("--at-least", "24"), Some(0));
+    assert_eq!(bounded("--at-least", "25"), Some(1));
+}
+
+#[test]
+fn at_most_sets_exit_code() {
+    assert_eq!(bounded("--at-most", "24"), Some(0));
+    assert_eq!(bounded("--at-most", "23"), Some(1));
+}
+
+#[test]
+fn bounds_print_nothing() {
+    let output = howoldami()
+        .args(["--birthday", "2000-01-01", "--at", "2024-06-01"])
+        .args(["--at-least", "18", "--at-most", "65"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn color_never_has_no_escapes() {
+    let output = howoldami()
+        .args(["--birthday", "2000-01-01", "--at", "2024-01-01"])
+        .args(["--color", "never"])
+        .output()
+        .unwrap();
+    assert_eq!(output.stdout, b"Happy birthday!\n24\n");
+}
+
+#[test]
+fn color_always_has_escapes() {
+    let output = howoldami()
+        .args(["--birthday", "2000-01-01", "--at", "2024-01-01"])
+        .args(["--color", "always"])
+        .output()
+        .unwrap();
+    assert!(output.stdout.contains(&0x1b));
+}
+
+#[test]
+fn porcelain_ignores_verbose() {
+    let output = howoldami()
+        .args(["--birthday", "2000-01-01", "--at", "2024-01-01"])
+        .args(["--porcelain", "--verbose", "--countdown", "--weekday"])
+        .output()
+        .unwrap();
+    assert_eq!(output.stdout, b"24\t2000-01-01\t2024-01-01\n");
+}
+
+#[test]
+fn output_writes_to_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("age.txt");
+    std::fs::write(&path, "stale contents that should be truncated\n").unwrap();
+    let status = howoldami()
+        .args(["--birthday", "2000-01-01", "--at", "2024-06-01"])
+        .arg("--output")
+        .arg(&path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "24\n");
+}
+
+#[test]
+fn output_to_missing_directory_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = howoldami()
+        .args(["--birthday", "2000-01-01", "--output"])
+        .arg(dir.path().join("missing").join("age.txt"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Could not create output file"));
+}
+
+#[test]
+fn missing_birthday_as_json() {
+    let config = tempfile::NamedTempFile::new().unwrap();
+    let output = howoldami()
+        .arg("--config")
+        .arg(config.path())
+        .args(["--error-format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["kind"], "missing_birthday");
+    assert!(error["error"].as_str().unwrap().contains("No birthday"));
+}
+
+#[test]
+fn age_at_each_date() {
+    let output = howoldami()
+        .args(["--birthday", "2000-06-15"])
+        .args([
+            "--at",
+            "2010-01-01",
+            "--at",
+            "2020-06-14",
+            "--at",
+            "2030-06-15",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "2010-01-01: 9\n2020-06-14: 19\nHappy birthday!\n2030-06-15: 30\n"
+    );
+}
+
+#[test]
+fn no_config_ignores_present_config() {
+    let xdg = tempfile::tempdir().unwrap();
+    let dir = xdg.path().join("howoldami");
+    std::fs::create_dir(&dir).unwrap();
+    std::fs::write(dir.join("config.toml"), "birthday = \"2000-01-01\"\n").unwrap();
+
+    let output = howoldami()
+        .env("XDG_CONFIG_HOME", xdg.path())
+        .args(["--at", "2024-06-01"])
+        .output()
+        .unwrap();
+    assert_eq!(output.stdout, b"24\n");
+
+    let output = howoldami()
+        .env("XDG_CONFIG_HOME", xdg.path())
+        .args(["--at", "2024-06-01", "--no-config"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+}
+
+fn stdout_of(args: &[&str]) -> String {
+    let output = howoldami().args(args).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn age_subcommand_matches_bare_invocation() {
+    let flags = [
+        "--no-config",
+        "--birthday",
+        "2000-01-01",
+        "--at",
+        "2024-06-01",
+    ];
+    let bare = stdout_of(&flags);
+    let explicit = stdout_of(&[&flags[..], &["age"]].concat());
+    assert_eq!(bare, explicit);
+    assert!(bare.contains("24"));
+}
+
+#[test]
+fn countdown_subcommand() {
+    let output = stdout_of(&[
+        "--no-config",
+        "--birthday",
+        "2000-01-01",
+        "--at",
+        "2024-12-30",
+        "countdown",
+    ]);
+    assert_eq!(output, "2 days until your next birthday\n");
+
+    for flags in [
+        &["--porcelain"][..],
+        &["--compact"],
+        &["--format-output", "json"],
+    ] {
+        let output = howoldami()
+            .args(["--no-config", "--birthday", "2000-01-01"])
+            .args(flags)
+            .arg("countdown")
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+    }
+}
+
+#[test]
+fn flags_after_the_subcommand() {
+    let output = stdout_of(&[
+        "countdown",
+        "--no-config",
+        "--birthday",
+        "2000-01-01",
+        "--at",
+        "2024-12-30",
+    ]);
+    assert_eq!(output, "2 days until your next birthday\n");
+    let output = stdout_of(&[
+        "age",
+        "--no-config",
+        "--birthday",
+        "2000-01-01",
+        "--at",
+        "2024-06-01",
+    ]);
+    assert_eq!(output, "24\n");
+}
+
+#[test]
+fn countdown_rejects_lists_of_birthdays() {
+    let config = household_config();
+    let household = howoldami()
+        .arg("--config")
+        .arg(config.path())
+        .arg("countdown")
+        .output()
+        .unwrap();
+    assert_eq!(household.status.code(), Some(1));
+    assert!(household.stdout.is_empty());
+
+    let mut batch = tempfile::NamedTempFile::new().unwrap();
+    writeln!(batch, "2000-01-01").unwrap();
+    let batch = howoldami()
+        .arg("--no-config")
+        .arg("--batch")
+        .arg(batch.path())
+        .arg("zodiac")
+        .output()
+        .unwrap();
+    assert_eq!(batch.status.code(), Some(1));
+    assert!(batch.stdout.is_empty());
+}
+
+#[test]
+fn jsonl_batch_prints_one_object_per_line() {
+    let mut batch = tempfile::NamedTempFile::new().unwrap();
+    writeln!(batch, "2000-01-01\nnot a date\n1990-06-15").unwrap();
+    let output = stdout_of(&[
+        "--no-config",
+        "--at",
+        "2024-06-01",
+        "--format-output",
+        "jsonl",
+        "--batch",
+        batch.path().to_str().unwrap(),
+    ]);
+    let lines: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["age"], 24);
+    assert_eq!(lines[1]["line"], 2);
+    assert!(lines[1]["error"].is_string());
+    assert_eq!(lines[2]["age"], 33);
+}
+
+#[test]
+fn birthday_file_fills_in_a_missing_birthday() {
+    let home = tempfile::tempdir().unwrap();
+    std::fs::write(home.path().join(".birthday"), "1990-06-15\n").unwrap();
+    let age = |args: &[&str]| {
+        let output = howoldami()
+            .env("HOME", home.path())
+            .args(["--at", "2024-06-01"])
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(age(&[]), "33\n");
+    assert_eq!(age(&["--birthday", "2000-01-01"]), "24\n");
+    assert_eq!(age(&["--no-config"]), "");
+}
+
+#[test]
+fn compact_has_no_trailing_newline() {
+    let flags = [
+        "--no-config",
+        "--birthday",
+        "2000-01-01",
+        "--at",
+        "2024-06-01",
+        "-vv",
+    ];
+    assert_eq!(stdout_of(&[&flags[..], &["--compact"]].concat()), "24");
+    assert_eq!(
+        stdout_of(&[&flags[..], &["--compact", "--unit", "days"]].concat()),
+        "8918"
+    );
+}
+
+#[test]
+fn notify_on_birthday_exit_codes() {
+    let notify = |at: &str| {
+        howoldami()
+            .args(["--no-config", "--birthday", "2000-06-15", "--at", at])
+            .arg("--notify-on-birthday")
+            .output()
+            .unwrap()
+    };
+    let today = notify("2024-06-15");
+    assert_eq!(today.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(today.stdout).unwrap(),
+        "Happy birthday!\n"
+    );
+
+    let not_today = notify("2024-06-14");
+    assert_eq!(not_today.status.code(), Some(3));
+    assert!(not_today.stdout.is_empty());
+}
+
+#[test]
+fn malformed_config_warns_but_missing_is_silent() {
+    let xdg = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        howoldami()
+            .env("XDG_CONFIG_HOME", xdg.path())
+            .args(["--birthday", "2000-01-01", "--at", "2024-06-01"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let missing = run(&[]);
+    assert_eq!(missing.stdout, b"24\n");
+    assert!(missing.stderr.is_empty());
+
+    let dir = xdg.path().join("howoldami");
+    std::fs::create_dir(&dir).unwrap();
+    std::fs::write(dir.join("config.toml"), "birthday = [oops\n").unwrap();
+    let malformed = run(&[]);
+    assert_eq!(malformed.stdout, b"24\n");
+    let warning = String::from_utf8(malformed.stderr).unwrap();
+    assert!(warning.starts_with("Ignoring config file"), "{}", warning);
+
+    assert_eq!(run(&["--strict-config"]).status.code(), Some(1));
+    assert_eq!(run(&["--strict"]).stdout, b"24\n");
+}
+
+#[test]
+fn interactive_skips_the_prompt_without_a_terminal() {
+    let output = howoldami()
+        .args(["--no-config", "--interactive"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("When is your birthday?"));
+}
+
+#[cfg(feature = "famous")]
+#[test]
+fn famous_birthdays() {
+    let age = stdout_of(&[
+        "--no-config",
+        "--famous",
+        "Alan Turing",
+        "--at",
+        "2012-07-01",
+    ]);
+    assert_eq!(age, "100\n");
+
+    let output = howoldami()
+        .args(["--no-config", "--famous", "Alan Turning"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Did you mean 'alan turing'?"));
+}
+
+#[test]
+fn birthdays_between() {
+    let birthdays = stdout_of(&[
+        "--no-config",
+        "--birthday",
+        "1996-02-29",
+        "--birthdays-between",
+        "2019-01-01",
+        "2021-12-31",
+        "--output-date-format",
+        "MDY/",
+    ]);
+    assert_eq!(
+        birthdays,
+        "You turn 23 on 02/28/2019\nYou turn 24 on 02/29/2020\nYou turn 25 on 02/28/2021\n"
+    );
+
+    let output = howoldami()
+        .args(["--no-config", "--birthday", "1996-02-29"])
+        .args(["--birthdays-between", "2021-12-31", "2019-01-01"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn timeline_modes() {
+    let timeline = |args: &[&str]| {
+        howoldami()
+            .args(["--no-config", "--birthday", "2000-01-01"])
+            .args(["--at", "2010-01-01", "--at", "2024-06-01"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    assert_eq!(
+        timeline(&["--porcelain"]).stdout,
+        b"10\t2000-01-01\t2010-01-01\n24\t2000-01-01\t2024-06-01\n"
+    );
+    assert_eq!(timeline(&["--compact"]).stdout, b"10\n24");
+
+    let bounded = timeline(&["--at-least", "18"]);
+    assert_eq!(bounded.status.code(), Some(1));
+    assert!(bounded.stdout.is_empty());
+    assert_eq!(timeline(&["--at-least", "10"]).status.code(), Some(0));
+
+    let notify = timeline(&["--notify-on-birthday"]);
+    assert_eq!(notify.stdout, b"2010-01-01: Happy birthday!\n");
+    let not_birthday = howoldami()
+        .args(["--no-config", "--birthday", "2000-01-01"])
+        .args(["--at", "2010-06-01", "--at", "2024-06-01"])
+        .arg("--notify-on-birthday")
+        .output()
+        .unwrap();
+    assert_eq!(not_birthday.status.code(), Some(3));
+}
+
+/// A config file listing an adult born 2000-01-01 and a child born 2011-01-01
+fn household_config() -> tempfile::NamedTempFile {
+    let mut config = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        config,
+        "format = {{ YMD = {{ separator = \"-\" }} }}\n\
+         [[person]]\nname = \"A\"\nbirthday = \"2000-01-01\"\n\
+         [[person]]\nname = \"B\"\nbirthday = \"2011-01-01\"\n"
+    )
+    .unwrap();
+    config
+}
+
+#[test]
+fn bounds_apply_to_every_person_and_batch_line() {
+    let config = household_config();
+    let household = |bound: &str| {
+        howoldami()
+            .arg("--config")
+            .arg(config.path())
+            .args(["--at", "2024-06-01", "--at-least", bound])
+            .output()
+            .unwrap()
+    };
+    let too_young = household("18");
+    assert_eq!(too_young.status.code(), Some(1));
+    assert!(too_young.stdout.is_empty());
+    assert_eq!(household("13").status.code(), Some(0));
+
+    let mut batch = tempfile::NamedTempFile::new().unwrap();
+    writeln!(batch, "2000-01-01\n2011-01-01").unwrap();
+    let batch = |bound: &str| {
+        howoldami()
+            .args(["--no-config", "--at", "2024-06-01", "--at-least", bound])
+            .arg("--batch")
+            .arg(batch.path())
+            .output()
+            .unwrap()
+    };
+    let too_young = batch("18");
+    assert_eq!(too_young.status.code(), Some(1));
+    assert!(too_young.stdout.is_empty());
+    assert_eq!(batch("13").status.code(), Some(0));
+}
+
+#[test]
+fn notify_on_birthday_for_a_household() {
+    let config = household_config();
+    let notify = |at: &str| {
+        howoldami()
+            .arg("--config")
+            .arg(config.path())
+            .args(["--at", at, "--notify-on-birthday"])
+            .output()
+            .unwrap()
+    };
+    let birthday = notify("2024-01-01");
+    assert_eq!(birthday.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(birthday.stdout).unwrap(),
+        "A: Happy birthday!\nB: Happy birthday!\n"
+    );
+
+    let no_birthday = notify("2024-06-01");
+    assert_eq!(no_birthday.status.code(), Some(3));
+    assert!(no_birthday.stdout.is_empty());
+}
+
+#[test]
+fn timeline_picks_the_result_like_a_single_date() {
+    let timeline = |args: &[&str]| {
+        howoldami()
+            .args(["--no-config", "--birthday", "2000-01-01"])
+            .args(["--at", "2010-01-01", "--at", "2024-06-01"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    assert_eq!(
+        String::from_utf8(timeline(&["--days-alive"]).stdout).unwrap(),
+        "Happy birthday!\n2010-01-01: 3653\n2024-06-01: 8918\n"
+    );
+    assert_eq!(
+        String::from_utf8(timeline(&["--template", "{years} on {today}"]).stdout).unwrap(),
+        "Happy birthday!\n2010-01-01: 10 on 2010-01-01\n2024-06-01: 24 on 2024-06-01\n"
+    );
+
+    for args in [
+        &["--other-birthday", "1990-01-01"][..],
+        &["--birthdays-between", "2019-01-01", "2021-12-31"],
+        &["countdown"],
+    ] {
+        let output = timeline(args);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+    }
+}
+
+#[test]
+fn household_and_batch_pick_the_result_like_a_single_birthday() {
+    let config = household_config();
+    let household = |args: &[&str]| {
+        let output = howoldami()
+            .arg("--config")
+            .arg(config.path())
+            .args(["--at", "2024-06-01"])
+            .args(args)
+            .output()
+            .unwrap();
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    assert_eq!(household(&["--compact"]).0, "24\n13");
+    assert_eq!(
+        household(&["--precise"]).0,
+        "A: 24 years, 5 months, 0 days\nB: 13 years, 5 months, 0 days\n"
+    );
+    assert_eq!(household(&["--template", "{years}y"]).0, "A: 24y\nB: 13y\n");
+    let (_, parsed) = household(&["--show-parsed"]);
+    assert!(
+        parsed.contains("B: Parsed birthday: 2011-01-01"),
+        "{}",
+        parsed
+    );
+
+    let mut batch = tempfile::NamedTempFile::new().unwrap();
+    writeln!(batch, "2000-01-01").unwrap();
+    let precise = stdout_of(&[
+        "--no-config",
+        "--at",
+        "2024-06-01",
+        "--precise",
+        "--batch",
+        batch.path().to_str().unwrap(),
+    ]);
+    assert_eq!(precise, "24 years, 5 months, 0 days\n");
+}
+
+#[test]
+fn detect_format_from_stdin() {
+    let mut child = howoldami()
+        .args(["--no-config", "--stdin", "--format", "detect"])
+        .args(["--at", "2024-06-01"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    writeln!(child.stdin.take().unwrap(), "31/12/1998").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.stdout, b"25\n");
+    assert!(output.stderr.is_empty());
+}